@@ -0,0 +1,354 @@
+//! Batched addition of affine points. Adding `n` pairs of points in affine
+//! coordinates requires `n` inversions in the base field, which are replaced
+//! here by a single inversion using Montgomery's trick.
+//!
+//! Formulas assume a short Weierstrass curve of the form `y^2 = x^3 + b`,
+//! which is the case for all the curves of the `pairing` crate.
+
+use crate::pairing::CurveAffine;
+use crate::pairing::ff::Field;
+
+use super::SynthesisError;
+
+/// Computes `out[i] = pairs[i].0 + pairs[i].1` using a single field inversion
+/// for the whole batch. Doublings, additions of inverse points and points at
+/// infinity are handled explicitly.
+///
+/// Panics if `pairs` and `out` have different lengths.
+pub fn batch_add_affine_pairs<G: CurveAffine>(
+    pairs: &[(G, G)],
+    out: &mut [G]
+) -> Result<(), SynthesisError>
+{
+    assert_eq!(pairs.len(), out.len(), "number of pairs and outputs must be equal");
+
+    let mut scratch = Vec::with_capacity(pairs.len());
+
+    batch_add(&mut PairsToOutput { pairs, out }, &mut scratch)
+}
+
+/// Computes `points[i] = points[i] + points[j]` for every `(i, j)` in `pairs`
+/// using a single field inversion for the whole batch. This is the form needed
+/// when reducing buckets of affine points without copying them out.
+///
+/// The destination `i` of a pair must not appear in any other pair, while
+/// `j == i` is allowed and doubles the point. `scratch` is cleared and reused
+/// to hold intermediate products, so passing the same vector to subsequent
+/// calls avoids reallocations.
+pub fn batch_add_affine_in_place<G: CurveAffine>(
+    points: &mut [G],
+    pairs: &[(usize, usize)],
+    scratch: &mut Vec<G::Base>
+) -> Result<(), SynthesisError>
+{
+    scratch.clear();
+
+    batch_add(&mut IndexedPairs { points, pairs }, scratch)
+}
+
+/// Source of the pairs to add and destination of the sums
+trait PairsStorage<G: CurveAffine> {
+    fn len(&self) -> usize;
+    fn pair(&self, i: usize) -> (G, G);
+    fn set_sum(&mut self, i: usize, sum: G);
+}
+
+struct PairsToOutput<'a, G: CurveAffine> {
+    pairs: &'a [(G, G)],
+    out: &'a mut [G]
+}
+
+impl<'a, G: CurveAffine> PairsStorage<G> for PairsToOutput<'a, G> {
+    fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    fn pair(&self, i: usize) -> (G, G) {
+        self.pairs[i]
+    }
+
+    fn set_sum(&mut self, i: usize, sum: G) {
+        self.out[i] = sum;
+    }
+}
+
+struct IndexedPairs<'a, G: CurveAffine> {
+    points: &'a mut [G],
+    pairs: &'a [(usize, usize)]
+}
+
+impl<'a, G: CurveAffine> PairsStorage<G> for IndexedPairs<'a, G> {
+    fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    fn pair(&self, i: usize) -> (G, G) {
+        let (dst, src) = self.pairs[i];
+
+        (self.points[dst], self.points[src])
+    }
+
+    fn set_sum(&mut self, i: usize, sum: G) {
+        self.points[self.pairs[i].0] = sum;
+    }
+}
+
+enum Addition<G: CurveAffine> {
+    /// The sum is known without a division
+    Done(G),
+    /// The slope is `num / den`
+    Slope(G::Base, G::Base)
+}
+
+fn classify<G: CurveAffine>(p: &G, q: &G) -> Addition<G> {
+    if p.is_zero() {
+        return Addition::Done(*q);
+    }
+
+    if q.is_zero() {
+        return Addition::Done(*p);
+    }
+
+    let (x1, y1) = p.into_xy_unchecked();
+    let (x2, y2) = q.into_xy_unchecked();
+
+    if x1 == x2 {
+        if y1 != y2 || y1.is_zero() {
+            // P + (-P)
+            return Addition::Done(G::zero());
+        }
+
+        // doubling, lambda = 3 * x^2 / (2 * y)
+        let mut num = x1;
+        num.square();
+        let mut tmp = num;
+        tmp.double();
+        num.add_assign(&tmp);
+
+        let mut den = y1;
+        den.double();
+
+        Addition::Slope(num, den)
+    } else {
+        // lambda = (y2 - y1) / (x2 - x1)
+        let mut num = y2;
+        num.sub_assign(&y1);
+
+        let mut den = x2;
+        den.sub_assign(&x1);
+
+        Addition::Slope(num, den)
+    }
+}
+
+fn batch_add<G: CurveAffine, S: PairsStorage<G>>(
+    storage: &mut S,
+    scratch: &mut Vec<G::Base>
+) -> Result<(), SynthesisError>
+{
+    // Montgomery's trick: accumulate the products of all denominators,
+    // keeping the prefix products in the scratch space
+    let mut acc = G::Base::one();
+    for i in 0..storage.len() {
+        let (p, q) = storage.pair(i);
+        if let Addition::Slope(_, den) = classify(&p, &q) {
+            scratch.push(acc);
+            acc.mul_assign(&den);
+        }
+    }
+
+    let mut inv = acc.inverse().ok_or(SynthesisError::DivisionByZero)?;
+
+    // walk backwards recovering individual inverses. Sums are only written
+    // here, after the pair is read, so in place destinations don't affect
+    // the remaining pairs
+    for i in (0..storage.len()).rev() {
+        let (p, q) = storage.pair(i);
+        let (num, den) = match classify(&p, &q) {
+            Addition::Done(sum) => {
+                storage.set_sum(i, sum);
+                continue;
+            },
+            Addition::Slope(num, den) => (num, den)
+        };
+
+        let prefix = scratch.pop().expect("a prefix product is stored for every division");
+        let mut den_inv = inv;
+        den_inv.mul_assign(&prefix);
+        inv.mul_assign(&den);
+
+        let mut lambda = num;
+        lambda.mul_assign(&den_inv);
+
+        let (x1, y1) = p.into_xy_unchecked();
+        let (x2, _) = q.into_xy_unchecked();
+
+        // x3 = lambda^2 - x1 - x2
+        let mut x3 = lambda;
+        x3.square();
+        x3.sub_assign(&x1);
+        x3.sub_assign(&x2);
+
+        // y3 = lambda * (x1 - x3) - y1
+        let mut y3 = x1;
+        y3.sub_assign(&x3);
+        y3.mul_assign(&lambda);
+        y3.sub_assign(&y1);
+
+        storage.set_sum(i, G::from_xy_unchecked(x3, y3));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+fn naive_add<G: CurveAffine>(p: &G, q: &G) -> G {
+    use crate::pairing::CurveProjective;
+
+    let mut tmp = p.into_projective();
+    tmp.add_assign_mixed(q);
+
+    tmp.into_affine()
+}
+
+#[test]
+fn test_batch_add_random_pairs() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::CurveProjective;
+    use crate::pairing::bn256::{G1, G1Affine};
+
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let pairs = (0..256).map(|_| (G1::rand(rng).into_affine(), G1::rand(rng).into_affine())).collect::<Vec<_>>();
+    let mut out = vec![G1Affine::zero(); pairs.len()];
+
+    batch_add_affine_pairs(&pairs, &mut out).unwrap();
+
+    for ((p, q), sum) in pairs.iter().zip(out.iter()) {
+        assert_eq!(naive_add(p, q), *sum);
+    }
+}
+
+#[test]
+fn test_batch_add_edge_cases() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::CurveProjective;
+    use crate::pairing::bn256::{G1, G1Affine};
+
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let p = G1::rand(rng).into_affine();
+    let q = G1::rand(rng).into_affine();
+    let mut minus_p = p;
+    minus_p.negate();
+
+    // mix special cases with regular additions in a single batch
+    let pairs = vec![
+        (p, q),
+        (p, minus_p),
+        (p, p),
+        (G1Affine::zero(), q),
+        (q, G1Affine::zero()),
+        (G1Affine::zero(), G1Affine::zero()),
+        (q, p)
+    ];
+    let mut out = vec![G1Affine::zero(); pairs.len()];
+
+    batch_add_affine_pairs(&pairs, &mut out).unwrap();
+
+    assert!(out[1].is_zero());
+    assert!(out[5].is_zero());
+    let mut doubled = p.into_projective();
+    doubled.double();
+    assert_eq!(out[2], doubled.into_affine());
+
+    for ((p, q), sum) in pairs.iter().zip(out.iter()) {
+        assert_eq!(naive_add(p, q), *sum);
+    }
+}
+
+#[test]
+#[should_panic(expected = "number of pairs and outputs must be equal")]
+fn test_batch_add_length_mismatch() {
+    use crate::pairing::bn256::G1Affine;
+
+    let pairs = vec![(G1Affine::one(), G1Affine::one()); 2];
+    let mut out = vec![G1Affine::zero(); 1];
+
+    let _ = batch_add_affine_pairs(&pairs, &mut out);
+}
+
+#[test]
+fn test_batch_add_in_place_random_pairs() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::CurveProjective;
+    use crate::pairing::bn256::G1;
+
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let points = (0..512).map(|_| G1::rand(rng).into_affine()).collect::<Vec<_>>();
+    // add the upper half into the lower half, in a shuffled order
+    let pairs = (0..256).map(|i| ((i * 37) % 256, 256 + i)).collect::<Vec<_>>();
+
+    let mut sums = points.clone();
+    let mut scratch = vec![];
+    batch_add_affine_in_place(&mut sums, &pairs, &mut scratch).unwrap();
+
+    for &(dst, src) in pairs.iter() {
+        assert_eq!(naive_add(&points[dst], &points[src]), sums[dst]);
+    }
+    assert_eq!(&sums[256..], &points[256..]);
+
+    // scratch space is reused by the next batch
+    batch_add_affine_in_place(&mut sums, &pairs, &mut scratch).unwrap();
+
+    for &(dst, src) in pairs.iter() {
+        let twice = naive_add(&naive_add(&points[dst], &points[src]), &points[src]);
+        assert_eq!(twice, sums[dst]);
+    }
+}
+
+#[test]
+fn test_batch_add_in_place_edge_cases() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::CurveProjective;
+    use crate::pairing::bn256::{G1, G1Affine};
+
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let p = G1::rand(rng).into_affine();
+    let q = G1::rand(rng).into_affine();
+    let mut minus_p = p;
+    minus_p.negate();
+
+    // same special cases as for pairs, plus doubling a point in place
+    let points = vec![
+        p, q,
+        p, minus_p,
+        p, p,
+        G1Affine::zero(), q,
+        q, G1Affine::zero(),
+        G1Affine::zero(), G1Affine::zero(),
+        q, p,
+        p
+    ];
+    let mut pairs = (0..7).map(|i| (2 * i, 2 * i + 1)).collect::<Vec<_>>();
+    pairs.push((14, 14));
+
+    let mut sums = points.clone();
+    batch_add_affine_in_place(&mut sums, &pairs, &mut vec![]).unwrap();
+
+    assert!(sums[2].is_zero());
+    assert!(sums[10].is_zero());
+    let mut doubled = p.into_projective();
+    doubled.double();
+    assert_eq!(sums[4], doubled.into_affine());
+    assert_eq!(sums[14], doubled.into_affine());
+
+    for &(dst, src) in pairs.iter() {
+        assert_eq!(naive_add(&points[dst], &points[src]), sums[dst]);
+    }
+
+    // an empty batch doesn't touch the points
+    batch_add_affine_in_place(&mut sums, &[], &mut vec![]).unwrap();
+}
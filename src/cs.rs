@@ -168,8 +168,12 @@ pub enum SynthesisError {
     IoError(io::Error),
     /// During verification, our verifying key was malformed.
     MalformedVerifyingKey,
+    /// During verification, our proof was malformed.
+    MalformedProof,
     /// During CRS generation, we observed an unconstrained auxillary variable
-    UnconstrainedVariable
+    UnconstrainedVariable,
+    /// During a long running computation, the caller requested cancellation
+    Aborted
 }
 
 impl From<io::Error> for SynthesisError {
@@ -188,7 +192,9 @@ impl SynthesisError {
             SynthesisError::UnexpectedIdentity => "encountered an identity element in the CRS",
             SynthesisError::IoError(_) => "encountered an I/O error",
             SynthesisError::MalformedVerifyingKey => "malformed verifying key",
-            SynthesisError::UnconstrainedVariable => "auxillary variable was unconstrained"
+            SynthesisError::MalformedProof => "malformed proof",
+            SynthesisError::UnconstrainedVariable => "auxillary variable was unconstrained",
+            SynthesisError::Aborted => "computation was aborted by the caller"
         }
     }
 }
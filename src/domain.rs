@@ -495,6 +495,40 @@ fn parallel_fft_consistency() {
     test_consistency::<Bls12, _>(rng);
 }
 
+#[test]
+fn fft_determinism_across_workers() {
+    use crate::pairing::bls12_381::{Bls12, Fr};
+    use rand::{XorShiftRng, SeedableRng, Rand};
+
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let workers = vec![
+        Worker::new_deterministic(1, None),
+        Worker::new_deterministic(4, None),
+        Worker::new_deterministic(4, Some(3)),
+        Worker::new_deterministic(3, Some(7)),
+    ];
+
+    for log_d in 0..10 {
+        let d = 1 << log_d;
+        let coeffs = (0..d).map(|_| Scalar::<Bls12>(Fr::rand(rng))).collect::<Vec<_>>();
+
+        let mut results = vec![];
+        for worker in workers.iter() {
+            let mut domain = EvaluationDomain::from_coeffs(coeffs.clone()).unwrap();
+            domain.coset_fft(worker);
+            domain.divide_by_z_on_coset(worker);
+            domain.icoset_fft(worker);
+
+            results.push(domain.into_coeffs());
+        }
+
+        for r in results.iter().skip(1) {
+            assert!(results[0] == *r);
+        }
+    }
+}
+
 #[test]
 fn test_field_element_multiplication_bn256() {
     use rand::{self, Rand};
@@ -340,7 +340,7 @@ pub fn generate_parameters<E, C>(
         // assembly.num_inputs + assembly.num_r1cs_constraints + assembly.num_r1cs_aux
     });
 
-    let worker = Worker::new();
+    let worker = Worker::current_or_new();
 
     // let z_at_tau = {
     //     // Compute powers of tau
@@ -243,7 +243,7 @@ pub fn generate_parameters<E, C>(
     let gamma_inverse = gamma.inverse().ok_or(SynthesisError::UnexpectedIdentity)?;
     let delta_inverse = delta.inverse().ok_or(SynthesisError::UnexpectedIdentity)?;
 
-    let worker = Worker::new();
+    let worker = Worker::current_or_new();
 
     let mut h = vec![E::G1::zero(); powers_of_tau.as_ref().len() - 1];
     {
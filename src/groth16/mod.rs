@@ -40,6 +40,18 @@ impl<E: Engine> PartialEq for Proof<E> {
 }
 
 impl<E: Engine> Proof<E> {
+    /// Checks that every point of the proof is on the curve, in the prime order
+    /// subgroup and is not the point at infinity. Proofs obtained through `read`
+    /// already satisfy it, but proofs assembled from raw coordinates may not.
+    /// Called by `verify_proof` and `verify_proofs_batched`.
+    pub fn validate(&self) -> Result<(), SynthesisError> {
+        if is_valid_point(&self.a) && is_valid_point(&self.b) && is_valid_point(&self.c) {
+            Ok(())
+        } else {
+            Err(SynthesisError::MalformedProof)
+        }
+    }
+
     pub fn write<W: Write>(
         &self,
         mut writer: W
@@ -138,6 +150,25 @@ impl<E: Engine> PartialEq for VerifyingKey<E> {
 }
 
 impl<E: Engine> VerifyingKey<E> {
+    /// Checks that every point of the key is on the curve, in the prime order
+    /// subgroup and is not the point at infinity.
+    /// Called by `prepare_verifying_key_checked`.
+    pub fn validate(&self) -> Result<(), SynthesisError> {
+        let valid = is_valid_point(&self.alpha_g1) &&
+            is_valid_point(&self.beta_g1) &&
+            is_valid_point(&self.beta_g2) &&
+            is_valid_point(&self.gamma_g2) &&
+            is_valid_point(&self.delta_g1) &&
+            is_valid_point(&self.delta_g2) &&
+            self.ic.iter().all(|p| is_valid_point(p));
+
+        if valid {
+            Ok(())
+        } else {
+            Err(SynthesisError::MalformedVerifyingKey)
+        }
+    }
+
     pub fn write<W: Write>(
         &self,
         mut writer: W
@@ -212,6 +243,17 @@ impl<E: Engine> VerifyingKey<E> {
     }
 }
 
+fn is_valid_point<G: CurveAffine>(point: &G) -> bool {
+    if point.is_zero() {
+        return false;
+    }
+
+    // checked construction performs both on-curve and subgroup checks
+    let (x, y) = point.into_xy_unchecked();
+
+    G::from_xy_checked(x, y).is_ok()
+}
+
 #[derive(Clone)]
 pub struct Parameters<E: Engine> {
     pub vk: VerifyingKey<E>,
@@ -573,4 +615,188 @@ mod test_with_bls12_381 {
             assert!(!verify_proof(&pvk, &proof, &[a]).unwrap());
         }
     }
-}
\ No newline at end of file
+
+    /// Proves knowledge of `a` and `b` such that `a * b` equals the public input `c`
+    struct MulCircuit<E: Engine> {
+        a: Option<E::Fr>,
+        b: Option<E::Fr>
+    }
+
+    impl<E: Engine> Circuit<E> for MulCircuit<E> {
+        fn synthesize<CS: ConstraintSystem<E>>(
+            self,
+            cs: &mut CS
+        ) -> Result<(), SynthesisError>
+        {
+            let a = cs.alloc(|| "a", || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+            let b = cs.alloc(|| "b", || self.b.ok_or(SynthesisError::AssignmentMissing))?;
+            let c = cs.alloc_input(|| "c", || {
+                let mut a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+                let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+
+                a.mul_assign(&b);
+                Ok(a)
+            })?;
+
+            cs.enforce(
+                || "a*b=c",
+                |lc| lc + a,
+                |lc| lc + b,
+                |lc| lc + c
+            );
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn batched_verification() {
+        use crate::worker::Worker;
+
+        let rng = &mut thread_rng();
+
+        let params = generate_random_parameters::<Bls12, _, _>(
+            MulCircuit { a: None, b: None },
+            rng
+        ).unwrap();
+
+        let pvk = prepare_verifying_key::<Bls12>(&params.vk);
+        let worker = Worker::new();
+
+        let mut proofs = vec![];
+        for _ in 0..8 {
+            let a = Fr::rand(rng);
+            let b = Fr::rand(rng);
+            let mut c = a;
+            c.mul_assign(&b);
+
+            let proof = create_random_proof(
+                MulCircuit {
+                    a: Some(a),
+                    b: Some(b)
+                },
+                &params,
+                rng
+            ).unwrap();
+
+            proofs.push((proof, vec![c]));
+        }
+
+        assert!(verify_proofs_batched(&pvk, &[], rng, &worker).unwrap());
+        assert!(verify_proofs_batched(&pvk, &proofs, rng, &worker).unwrap());
+        assert!(verify_proofs_batched_with_blame(&pvk, &proofs, rng, &worker).unwrap().is_empty());
+
+        // wrong public inputs for some of the proofs
+        proofs[3].1[0] = Fr::rand(rng);
+        proofs[6].1[0].add_assign(&Fr::one());
+
+        assert!(!verify_proofs_batched(&pvk, &proofs, rng, &worker).unwrap());
+        assert_eq!(verify_proofs_batched_with_blame(&pvk, &proofs, rng, &worker).unwrap(), vec![3, 6]);
+
+        // a proof can't be used for the statement of another one
+        proofs[3].1 = proofs[2].1.clone();
+        proofs[6].1 = proofs[5].1.clone();
+        proofs.swap(0, 1);
+
+        assert_eq!(verify_proofs_batched_with_blame(&pvk, &proofs, rng, &worker).unwrap(), vec![0, 1, 3, 6]);
+
+        // malformed inputs are rejected
+        proofs[2].1.push(Fr::one());
+        assert!(verify_proofs_batched(&pvk, &proofs, rng, &worker).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "multicore")]
+    fn proving_reuses_current_worker() {
+        crate::tests::run_isolated("groth16::test_with_bls12_381::proving_reuses_current_worker_isolated");
+    }
+
+    #[test]
+    #[ignore]
+    #[cfg(feature = "multicore")]
+    fn proving_reuses_current_worker_isolated() {
+        use crate::worker::Worker;
+
+        // pools created on the worker threads are counted as well,
+        // so the test must be the only one in the process
+        if !crate::tests::is_isolated("groth16::test_with_bls12_381::proving_reuses_current_worker_isolated") {
+            return;
+        }
+
+        let rng = &mut thread_rng();
+
+        let worker = Worker::new_deterministic(2, None);
+        let pools = crate::multicore::pools_created();
+
+        worker.enter(|| {
+            let params = generate_random_parameters::<Bls12, _, _>(
+                MulCircuit { a: None, b: None },
+                rng
+            ).unwrap();
+
+            let pvk = prepare_verifying_key::<Bls12>(&params.vk);
+
+            let a = Fr::rand(rng);
+            let b = Fr::rand(rng);
+            let mut c = a;
+            c.mul_assign(&b);
+
+            let proof = create_random_proof(
+                MulCircuit {
+                    a: Some(a),
+                    b: Some(b)
+                },
+                &params,
+                rng
+            ).unwrap();
+
+            assert!(verify_proof(&pvk, &proof, &[c]).unwrap());
+        });
+
+        assert_eq!(pools, crate::multicore::pools_created());
+    }
+
+    #[test]
+    #[ignore]
+    fn batched_verification_speed() {
+        use crate::worker::Worker;
+        use std::time::Instant;
+
+        const SAMPLES: usize = 64;
+
+        let rng = &mut thread_rng();
+
+        let params = generate_random_parameters::<Bls12, _, _>(
+            MulCircuit { a: None, b: None },
+            rng
+        ).unwrap();
+
+        let pvk = prepare_verifying_key::<Bls12>(&params.vk);
+        let worker = Worker::new();
+
+        let proofs = (0..SAMPLES).map(|_| {
+            let a = Fr::rand(rng);
+            let b = Fr::rand(rng);
+            let mut c = a;
+            c.mul_assign(&b);
+
+            let proof = create_random_proof(MulCircuit { a: Some(a), b: Some(b) }, &params, rng).unwrap();
+
+            (proof, vec![c])
+        }).collect::<Vec<_>>();
+
+        for &n in [1, 4, 16, 64].iter() {
+            let start = Instant::now();
+            for (proof, inputs) in proofs[..n].iter() {
+                assert!(verify_proof(&pvk, proof, inputs).unwrap());
+            }
+            let one_by_one = start.elapsed().as_nanos();
+
+            let start = Instant::now();
+            assert!(verify_proofs_batched(&pvk, &proofs[..n], rng, &worker).unwrap());
+            let batched = start.elapsed().as_nanos();
+
+            println!("{} proofs: {} ns one by one, {} ns batched", n, one_by_one, batched);
+        }
+    }
+}
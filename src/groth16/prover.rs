@@ -105,27 +105,6 @@ pub(crate) fn field_elements_into_representations<E: Engine>(
     Ok(representations)
 }
 
-pub(crate) fn scalars_into_representations<E: Engine>(
-    worker: &Worker,
-    scalars: Vec<Scalar<E>>
-) -> Result<Vec<<E::Fr as PrimeField>::Repr>, SynthesisError>
-{   
-    let mut representations = vec![<E::Fr as PrimeField>::Repr::default(); scalars.len()];
-    worker.scope(scalars.len(), |scope, chunk| {
-        for (scalar, repr) in scalars.chunks(chunk)
-                    .zip(representations.chunks_mut(chunk)) {
-            scope.spawn(move |_| {
-                for (scalar, repr) in scalar.iter()
-                                        .zip(repr.iter_mut()) {
-                    *repr = scalar.0.into_repr();
-                }
-            });
-        }
-    });
-
-    Ok(representations)
-}
-
 // This is a proving assignment with densities precalculated
 pub struct PreparedProver<E: Engine>{
     pub assignment: ProvingAssignment<E>,
@@ -205,7 +184,7 @@ impl<E:Engine> PreparedProver<E> {
     ) -> Result<Proof<E>, SynthesisError>
     {
         let prover = self.assignment;
-        let worker = Worker::new();
+        let worker = Worker::current_or_new();
 
         let vk = params.get_vk(prover.input_assignment.len())?;
 
@@ -240,12 +219,11 @@ impl<E:Engine> PreparedProver<E> {
             let mut a = a.into_coeffs();
             let a_len = a.len() - 1;
             a.truncate(a_len);
-            // TODO: parallelize if it's even helpful
-            // TODO: in large settings it may worth to parallelize
-            let a = Arc::new(scalars_into_representations::<E>(&worker, a)?);
-            // let a = Arc::new(a.into_iter().map(|s| s.0.into_repr()).collect::<Vec<_>>());
+            // scalars are converted into representations by the multiexp itself, block by block,
+            // and unwrapping them reuses the allocation of the coefficients
+            let a = Arc::new(a.into_iter().map(|s| s.0).collect::<Vec<_>>());
 
-            multiexp(&worker, params.get_h(a.len())?, FullDensity, a)
+            multiexp_from_field_elements(&worker, params.get_h(a.len())?, FullDensity, a)
         };
 
         elog_verbose!("{} seconds for prover for H evaluation (mostly FFT)", _stopwatch.elapsed());
@@ -328,3 +328,102 @@ fn test_xordemo() {
         &[Fr::one()]
     ).unwrap());
 }
+
+#[test]
+fn test_proof_validation_rejects_invalid_points() {
+    use crate::pairing::{CurveAffine, CurveProjective};
+    use crate::pairing::ff::SqrtField;
+    use crate::pairing::bn256::{Bn256, Fq, Fq2, G1, G1Affine, G2, G2Affine};
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use super::{Proof, VerifyingKey, prepare_verifying_key, prepare_verifying_key_checked, verify_proofs_batched};
+
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let proof = Proof::<Bn256> {
+        a: G1::rand(rng).into_affine(),
+        b: G2::rand(rng).into_affine(),
+        c: G1::rand(rng).into_affine()
+    };
+
+    assert!(proof.validate().is_ok());
+
+    // point at infinity
+    let mut infinity = proof.clone();
+    infinity.c = G1Affine::zero();
+    assert!(infinity.validate().is_err());
+
+    // point that is not on the curve
+    let mut off_curve = proof.clone();
+    let (x, mut y) = off_curve.a.into_xy_unchecked();
+    y.add_assign(&Fq::one());
+    off_curve.a = G1Affine::from_xy_unchecked(x, y);
+    assert!(off_curve.validate().is_err());
+
+    // point on the twist that is outside of the prime order subgroup
+    let (x0, y0) = proof.b.into_xy_unchecked();
+    let mut b = y0;
+    b.square();
+    let mut x0_cubed = x0;
+    x0_cubed.square();
+    x0_cubed.mul_assign(&x0);
+    b.sub_assign(&x0_cubed);
+
+    let mut x = x0;
+    let wrong_subgroup = loop {
+        x.add_assign(&Fq2::one());
+        let mut rhs = x;
+        rhs.square();
+        rhs.mul_assign(&x);
+        rhs.add_assign(&b);
+        if let Some(y) = rhs.sqrt() {
+            break G2Affine::from_xy_unchecked(x, y);
+        }
+    };
+
+    let mut not_in_subgroup = proof.clone();
+    not_in_subgroup.b = wrong_subgroup;
+    assert!(not_in_subgroup.validate().is_err());
+
+    // verification rejects the proof before doing any pairings
+    let vk = VerifyingKey::<Bn256> {
+        alpha_g1: G1::rand(rng).into_affine(),
+        beta_g1: G1::rand(rng).into_affine(),
+        beta_g2: G2::rand(rng).into_affine(),
+        gamma_g2: G2::rand(rng).into_affine(),
+        delta_g1: G1::rand(rng).into_affine(),
+        delta_g2: G2::rand(rng).into_affine(),
+        ic: vec![G1::rand(rng).into_affine()]
+    };
+
+    let pvk = prepare_verifying_key(&vk);
+    match verify_proof(&pvk, &not_in_subgroup, &[]) {
+        Err(SynthesisError::MalformedProof) => {},
+        _ => panic!("proof with a point outside of the subgroup must be rejected")
+    }
+
+    let batch = vec![(proof.clone(), vec![]), (off_curve.clone(), vec![])];
+    let worker = crate::worker::Worker::new();
+    match verify_proofs_batched(&pvk, &batch, rng, &worker) {
+        Err(SynthesisError::MalformedProof) => {},
+        _ => panic!("batch with a point that is not on the curve must be rejected")
+    }
+
+    // verifying keys are checked the same way
+    assert!(vk.validate().is_ok());
+    assert!(prepare_verifying_key_checked(&vk).is_ok());
+
+    let mut bad_vk = vk.clone();
+    bad_vk.gamma_g2 = wrong_subgroup;
+    match prepare_verifying_key_checked(&bad_vk) {
+        Err(SynthesisError::MalformedVerifyingKey) => {},
+        _ => panic!("key with a point outside of the subgroup must be rejected")
+    }
+
+    let mut bad_vk = vk.clone();
+    bad_vk.ic[0] = off_curve.a;
+    assert!(prepare_verifying_key_checked(&bad_vk).is_err());
+
+    let mut bad_vk = vk;
+    bad_vk.delta_g1 = G1Affine::zero();
+    assert!(prepare_verifying_key_checked(&bad_vk).is_err());
+}
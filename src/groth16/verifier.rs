@@ -1,10 +1,12 @@
+use rand::Rng;
+
 use crate::pairing::{
     Engine,
     CurveProjective,
     CurveAffine
 };
 
-use crate::pairing::ff::{PrimeField};
+use crate::pairing::ff::{PrimeField, Field};
 
 use super::{
     Proof,
@@ -16,6 +18,14 @@ use crate::{
     SynthesisError
 };
 
+use crate::wnaf::mul_wnaf;
+
+use crate::multiexp::dense_multiexp;
+
+use crate::worker::{
+    Worker
+};
+
 pub fn prepare_verifying_key<E: Engine>(
     vk: &VerifyingKey<E>
 ) -> PreparedVerifyingKey<E>
@@ -33,11 +43,41 @@ pub fn prepare_verifying_key<E: Engine>(
     }
 }
 
+/// Same as `prepare_verifying_key`, but first checks that every point of the key
+/// is valid. `VerifyingKey::read` checks that the points are on the curve and in the
+/// subgroup, but accepts the point at infinity for all of them except `ic`, so keys
+/// from untrusted sources should be prepared with this function.
+pub fn prepare_verifying_key_checked<E: Engine>(
+    vk: &VerifyingKey<E>
+) -> Result<PreparedVerifyingKey<E>, SynthesisError>
+{
+    vk.validate()?;
+
+    Ok(prepare_verifying_key(vk))
+}
+
+/// Verifies the proof. Points of the proof are checked with `Proof::validate` first,
+/// so proofs assembled from raw coordinates are rejected with `SynthesisError::MalformedProof`
+/// if any of their points is not a valid group element.
 pub fn verify_proof<'a, E: Engine>(
     pvk: &'a PreparedVerifyingKey<E>,
     proof: &Proof<E>,
     public_inputs: &[E::Fr]
 ) -> Result<bool, SynthesisError>
+{
+    proof.validate()?;
+
+    verify_proof_unchecked(pvk, proof, public_inputs)
+}
+
+/// Same as `verify_proof`, but skips the check of the points of the proof. Should only
+/// be used for proofs that are known to be valid, e.g. obtained through `Proof::read`,
+/// which performs the same checks while decompressing the points.
+pub fn verify_proof_unchecked<'a, E: Engine>(
+    pvk: &'a PreparedVerifyingKey<E>,
+    proof: &Proof<E>,
+    public_inputs: &[E::Fr]
+) -> Result<bool, SynthesisError>
 {
     if (public_inputs.len() + 1) != pvk.ic.len() {
         return Err(SynthesisError::MalformedVerifyingKey);
@@ -46,7 +86,7 @@ pub fn verify_proof<'a, E: Engine>(
     let mut acc = pvk.ic[0].into_projective();
 
     for (i, b) in public_inputs.iter().zip(pvk.ic.iter().skip(1)) {
-        acc.add_assign(&b.mul(i.into_repr()));
+        acc.add_assign(&mul_wnaf(b, &i.into_repr()));
     }
 
     // The original verification equation is:
@@ -65,3 +105,136 @@ pub fn verify_proof<'a, E: Engine>(
         ].iter())
     ).unwrap() == pvk.alpha_g1_beta_g2)
 }
+
+/// Verifies a batch of proofs against the same verifying key. Verification equations
+/// of the proofs are combined with random coefficients, so the whole batch takes a single
+/// final exponentiation and the public inputs and `C` elements of all the proofs are
+/// accumulated with two multiexps. Returns `true` only if every proof in the batch is valid.
+/// Like `verify_proof` it rejects proofs with invalid points.
+pub fn verify_proofs_batched<'a, E: Engine, R: Rng>(
+    pvk: &'a PreparedVerifyingKey<E>,
+    proofs: &[(Proof<E>, Vec<E::Fr>)],
+    rng: &mut R,
+    worker: &Worker
+) -> Result<bool, SynthesisError>
+{
+    for (proof, _) in proofs.iter() {
+        proof.validate()?;
+    }
+
+    verify_proofs_batched_unchecked(pvk, proofs, rng, worker)
+}
+
+/// Same as `verify_proofs_batched`, but skips the check of the points of the proofs,
+/// see `verify_proof_unchecked`.
+pub fn verify_proofs_batched_unchecked<'a, E: Engine, R: Rng>(
+    pvk: &'a PreparedVerifyingKey<E>,
+    proofs: &[(Proof<E>, Vec<E::Fr>)],
+    rng: &mut R,
+    worker: &Worker
+) -> Result<bool, SynthesisError>
+{
+    for (_, public_inputs) in proofs.iter() {
+        if (public_inputs.len() + 1) != pvk.ic.len() {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+    }
+
+    if proofs.is_empty() {
+        return Ok(true);
+    }
+
+    // Every proof satisfies
+    // A_i * B_i + inputs_i * (-gamma) + C_i * (-delta) = alpha * beta
+    // so for random r_i it should hold that
+    // sum(r_i * A_i * B_i) + sum(r_i * inputs_i) * (-gamma) + sum(r_i * C_i) * (-delta) = sum(r_i) * alpha * beta
+    let randomness: Vec<E::Fr> = (0..proofs.len()).map(|_| rng.gen()).collect();
+
+    // the first coefficient is for the implicit "one" input and is equal to sum(r_i)
+    let mut input_coeffs = vec![E::Fr::zero(); pvk.ic.len()];
+    for ((_, public_inputs), r) in proofs.iter().zip(randomness.iter()) {
+        input_coeffs[0].add_assign(r);
+
+        for (coeff, input) in input_coeffs[1..].iter_mut().zip(public_inputs.iter()) {
+            let mut tmp = *input;
+            tmp.mul_assign(r);
+            coeff.add_assign(&tmp);
+        }
+    }
+
+    let randomness_sum = input_coeffs[0];
+
+    let input_coeffs = input_coeffs.into_iter().map(|c| c.into_repr()).collect::<Vec<_>>();
+    let acc_inputs = dense_multiexp(worker, &pvk.ic, &input_coeffs)?;
+
+    let c_bases = proofs.iter().map(|(proof, _)| proof.c).collect::<Vec<_>>();
+    let randomness = randomness.into_iter().map(|r| r.into_repr()).collect::<Vec<_>>();
+    let acc_c = dense_multiexp(worker, &c_bases, &randomness)?;
+
+    let mut a_prepared = Vec::with_capacity(proofs.len());
+    let mut b_prepared = Vec::with_capacity(proofs.len());
+    for ((proof, _), r) in proofs.iter().zip(randomness.iter()) {
+        a_prepared.push(mul_wnaf(&proof.a, r).into_affine().prepare());
+        b_prepared.push(proof.b.prepare());
+    }
+
+    let acc_inputs = acc_inputs.into_affine().prepare();
+    let acc_c = acc_c.into_affine().prepare();
+
+    let mut pairs = a_prepared.iter().zip(b_prepared.iter()).collect::<Vec<_>>();
+    pairs.push((&acc_inputs, &pvk.neg_gamma_g2));
+    pairs.push((&acc_c, &pvk.neg_delta_g2));
+
+    let lhs = E::final_exponentiation(&E::miller_loop(pairs.iter())).unwrap();
+
+    Ok(lhs == pvk.alpha_g1_beta_g2.pow(randomness_sum.into_repr()))
+}
+
+/// Same as `verify_proofs_batched`, but if the batch fails it's bisected to find the
+/// invalid proofs. Returns their indexes, so an empty result means that all proofs are valid.
+pub fn verify_proofs_batched_with_blame<'a, E: Engine, R: Rng>(
+    pvk: &'a PreparedVerifyingKey<E>,
+    proofs: &[(Proof<E>, Vec<E::Fr>)],
+    rng: &mut R,
+    worker: &Worker
+) -> Result<Vec<usize>, SynthesisError>
+{
+    // points are checked once here rather than by every step of the bisection
+    for (proof, _) in proofs.iter() {
+        proof.validate()?;
+    }
+
+    let mut invalid = vec![];
+
+    find_invalid_proofs(pvk, proofs, 0, rng, worker, &mut invalid)?;
+
+    Ok(invalid)
+}
+
+fn find_invalid_proofs<'a, E: Engine, R: Rng>(
+    pvk: &'a PreparedVerifyingKey<E>,
+    proofs: &[(Proof<E>, Vec<E::Fr>)],
+    offset: usize,
+    rng: &mut R,
+    worker: &Worker,
+    invalid: &mut Vec<usize>
+) -> Result<(), SynthesisError>
+{
+    match proofs.len() {
+        0 => {},
+        1 => {
+            if !verify_proof_unchecked(pvk, &proofs[0].0, &proofs[0].1)? {
+                invalid.push(offset);
+            }
+        },
+        _ => {
+            if !verify_proofs_batched_unchecked(pvk, proofs, rng, worker)? {
+                let mid = proofs.len() / 2;
+                find_invalid_proofs(pvk, &proofs[..mid], offset, rng, worker, invalid)?;
+                find_invalid_proofs(pvk, &proofs[mid..], offset + mid, rng, worker, invalid)?;
+            }
+        }
+    }
+
+    Ok(())
+}
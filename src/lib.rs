@@ -21,7 +21,10 @@ pub mod sonic;
 
 mod group;
 pub mod source;
-mod multiexp;
+pub mod multiexp;
+pub mod affine_batch_ops;
+pub mod wnaf;
+pub mod prelude;
 
 #[cfg(test)]
 mod tests;
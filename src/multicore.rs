@@ -11,6 +11,7 @@ extern crate crossbeam;
 use std::future::{Future};
 use std::task::{Context, Poll};
 use std::pin::{Pin};
+use std::cell::RefCell;
 
 use self::crossbeam::thread::{Scope};
 
@@ -19,9 +20,29 @@ use self::futures::channel::oneshot::{channel, Sender, Receiver};
 use self::futures::executor::{block_on};
 use self::futures::executor::{ThreadPool};
 
+thread_local! {
+    // worker installed by `Worker::enter` on this thread
+    static CURRENT_WORKER: RefCell<Option<Worker>> = RefCell::new(None);
+}
+
+#[cfg(test)]
+static POOLS_CREATED: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// Number of thread pools created by the process on any thread, used to check that
+/// code reuses the ambient worker. Tests that rely on it should be run with `run_isolated`
+#[cfg(test)]
+pub(crate) fn pools_created() -> usize {
+    POOLS_CREATED.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+/// Bounds the number of chunks (and so threads) that `scope` produces
+/// when the chunk size is overridden
+const MAX_CHUNKS_PER_CPU: usize = 4;
+
 #[derive(Clone)]
 pub struct Worker {
     cpus: usize,
+    chunk_size_override: Option<usize>,
     pool: ThreadPool
 }
 
@@ -32,8 +53,12 @@ impl Worker {
     // CPUs configured.
     
     pub(crate) fn new_with_cpus(cpus: usize) -> Worker {
+        #[cfg(test)]
+        POOLS_CREATED.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
         Worker {
             cpus: cpus,
+            chunk_size_override: None,
             pool: ThreadPool::builder().pool_size(cpus).create().expect("should create a thread pool for futures execution"),
         }
     }
@@ -42,6 +67,55 @@ impl Worker {
         Self::new_with_cpus(num_cpus::get())
     }
 
+    /// Creates a worker that doesn't depend on the machine it runs on: its pool has
+    /// `num_cpus` threads and, if `chunk_size_override` is given, every `scope`
+    /// splits the work into chunks of this size. Callers of `scope` run every chunk
+    /// on its own thread, so the chunk size is increased if it would produce more than
+    /// `MAX_CHUNKS_PER_CPU * num_cpus` chunks. Intended for reproducing and debugging
+    /// results that depend on the chunking.
+    pub fn new_deterministic(num_cpus: usize, chunk_size_override: Option<usize>) -> Worker {
+        assert!(num_cpus > 0, "worker requires at least one thread");
+        if let Some(chunk_size) = chunk_size_override {
+            assert!(chunk_size > 0, "chunk size must be positive");
+        }
+
+        let mut worker = Self::new_with_cpus(num_cpus);
+        worker.chunk_size_override = chunk_size_override;
+
+        worker
+    }
+
+    /// Returns the worker installed on this thread by `enter`, if any
+    pub fn try_current() -> Option<Worker> {
+        CURRENT_WORKER.with(|w| w.borrow().clone())
+    }
+
+    /// Returns the worker installed on this thread by `enter` or creates a new one.
+    /// Code that needs a worker but doesn't take one as an argument should use it
+    /// instead of `Worker::new()`, so it runs on the caller's thread pool.
+    pub fn current_or_new() -> Worker {
+        Self::try_current().unwrap_or_else(Self::new)
+    }
+
+    /// Installs this worker as the current one on this thread while `f` runs
+    pub fn enter<F, R>(&self, f: F) -> R
+        where F: FnOnce() -> R
+    {
+        // restores the previous worker even if `f` panics
+        struct Restore(Option<Worker>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let previous = self.0.take();
+                CURRENT_WORKER.with(|w| *w.borrow_mut() = previous);
+            }
+        }
+
+        let _restore = Restore(CURRENT_WORKER.with(|w| w.replace(Some(self.clone()))));
+
+        f()
+    }
+
     pub fn log_num_cpus(&self) -> u32 {
         log2_floor(self.cpus)
     }
@@ -78,7 +152,11 @@ impl Worker {
     ) -> R
         where F: FnOnce(&Scope<'a>, usize) -> R
     {
-        let chunk_size = if elements < self.cpus {
+        let chunk_size = if let Some(chunk_size) = self.chunk_size_override {
+            let max_chunks = self.cpus * MAX_CHUNKS_PER_CPU;
+
+            std::cmp::max(chunk_size, (elements + max_chunks - 1) / max_chunks)
+        } else if elements < self.cpus {
             1
         } else {
             elements / self.cpus
@@ -145,6 +223,52 @@ fn test_log2_floor() {
     assert_eq!(log2_floor(8), 3);
 }
 
+#[test]
+fn test_enter_installs_current_worker() {
+    crate::tests::run_isolated("multicore::test_enter_installs_current_worker_isolated");
+}
+
+#[test]
+#[ignore]
+fn test_enter_installs_current_worker_isolated() {
+    if !crate::tests::is_isolated("multicore::test_enter_installs_current_worker_isolated") {
+        return;
+    }
+
+    assert!(Worker::try_current().is_none());
+
+    let worker = Worker::new_deterministic(2, None);
+    let pools = pools_created();
+
+    worker.enter(|| {
+        assert_eq!(Worker::current_or_new().cpus, 2);
+
+        Worker::new_deterministic(3, None).enter(|| {
+            assert_eq!(Worker::current_or_new().cpus, 3);
+        });
+
+        assert_eq!(Worker::current_or_new().cpus, 2);
+    });
+
+    assert_eq!(pools + 1, pools_created());
+    assert!(Worker::try_current().is_none());
+}
+
+#[test]
+fn test_chunk_size_override_is_bounded() {
+    let worker = Worker::new_deterministic(2, Some(1));
+
+    // small inputs are split exactly as requested
+    assert_eq!(worker.scope(8, |_, chunk| chunk), 1);
+
+    let elements = 1 << 20;
+    let chunk = worker.scope(elements, |_, chunk| chunk);
+    assert!((elements + chunk - 1) / chunk <= 2 * MAX_CHUNKS_PER_CPU);
+
+    let worker = Worker::new_deterministic(2, Some(1 << 19));
+    assert_eq!(worker.scope(elements, |_, chunk| chunk), 1 << 19);
+}
+
 #[test]
 fn test_trivial_spawning() {
     use self::futures::executor::block_on;
@@ -10,7 +10,9 @@ use crate::pairing::ff::{
     PrimeFieldRepr,
     ScalarEngine};
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
 use super::source::*;
 use std::future::{Future};
 use std::task::{Context, Poll};
@@ -18,7 +20,7 @@ use std::pin::{Pin};
 
 extern crate futures;
 
-use self::futures::future::{join_all, JoinAll};
+use self::futures::future::{try_join_all, poll_fn, TryJoinAll};
 use self::futures::executor::block_on;
 
 use super::worker::{Worker, WorkerFuture};
@@ -27,6 +29,85 @@ use super::SynthesisError;
 
 use cfg_if;
 
+/// Receives progress notifications from a running multiexp. Callbacks are invoked
+/// from the worker threads, so implementations should be cheap
+pub trait MultiexpObserver: Send + Sync {
+    /// Called when the worker starts the region of `c` bits starting at bit `skip`
+    fn on_chunk_started(&self, skip: u32, c: u32);
+
+    /// Called when the region starting at bit `skip` is fully accumulated
+    fn on_chunk_finished(&self, skip: u32);
+}
+
+/// Shared flag that allows the caller to abort a running multiexp. Every region
+/// checks it once per `CANCELLATION_CHECK_INTERVAL` scalars and resolves
+/// with `SynthesisError::Aborted` after cancellation
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+const CANCELLATION_CHECK_INTERVAL: usize = 1 << 16;
+
+/// Strategy used to process every region of the multiexp
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultiexpAlgorithm {
+    /// Pippenger's buckets
+    Buckets,
+    /// Pippenger's buckets with the next bucket prefetched. Prefetching only
+    /// happens with the `nightly` feature, otherwise it's the same as `Buckets`
+    BucketsPrefetch
+}
+
+impl Default for MultiexpAlgorithm {
+    fn default() -> Self {
+        if cfg!(feature = "nightly") {
+            MultiexpAlgorithm::BucketsPrefetch
+        } else {
+            MultiexpAlgorithm::Buckets
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct MultiexpHooks {
+    observer: Option<Arc<dyn MultiexpObserver>>,
+    cancellation: Option<CancellationToken>
+}
+
+impl MultiexpHooks {
+    fn chunk_started(&self, skip: u32, c: u32) {
+        if let Some(observer) = self.observer.as_ref() {
+            observer.on_chunk_started(skip, c);
+        }
+    }
+
+    fn chunk_finished(&self, skip: u32) {
+        if let Some(observer) = self.observer.as_ref() {
+            observer.on_chunk_finished(skip);
+        }
+    }
+
+    fn check_cancelled(&self) -> Result<(), SynthesisError> {
+        match self.cancellation.as_ref() {
+            Some(token) if token.is_cancelled() => Err(SynthesisError::Aborted),
+            _ => Ok(())
+        }
+    }
+}
+
 /// This genious piece of code works in the following way:
 /// - choose `c` - the bit length of the region that one thread works on
 /// - make `2^c - 1` buckets and initialize them with `G = infinity` (that's equivalent of zero)
@@ -57,7 +138,6 @@ use cfg_if;
 /// - accumulators over each set of buckets will have an implicit factor of `(2^c)^i`, so before summing thme up
 /// "higher" accumulators must be doubled `c` times
 ///
-#[cfg(not(feature = "nightly"))]
 fn multiexp_inner<Q, D, G, S>(
     pool: &Worker,
     bases: S,
@@ -65,7 +145,8 @@ fn multiexp_inner<Q, D, G, S>(
     exponents: Arc<Vec<<G::Scalar as PrimeField>::Repr>>,
     skip: u32,
     c: u32,
-    handle_trivial: bool
+    handle_trivial: bool,
+    hooks: MultiexpHooks
 ) -> WorkerFuture< <G as CurveAffine>::Projective, SynthesisError>
     where for<'a> &'a Q: QueryDensity,
           D: Send + Sync + 'static + Clone + AsRef<Q>,
@@ -80,6 +161,8 @@ fn multiexp_inner<Q, D, G, S>(
 
         // This is a Pippenger’s algorithm
         pool.compute(move || {
+            hooks.chunk_started(skip, c);
+
             // Accumulate the result
             let mut acc = G::Projective::zero();
 
@@ -96,7 +179,11 @@ fn multiexp_inner<Q, D, G, S>(
             let one = <G::Engine as ScalarEngine>::Fr::one().into_repr();
 
             // Sort the bases into buckets
-            for (&exp, density) in exponents.iter().zip(density_map.as_ref().iter()) {
+            for (i, (&exp, density)) in exponents.iter().zip(density_map.as_ref().iter()).enumerate() {
+                if i % CANCELLATION_CHECK_INTERVAL == 0 {
+                    hooks.check_cancelled()?;
+                }
+
                 // Go over density and exponents
                 if density {
                     if exp == zero {
@@ -136,6 +223,8 @@ fn multiexp_inner<Q, D, G, S>(
                 acc.add_assign(&running_sum);
             }
 
+            hooks.chunk_finished(skip);
+
             Ok(acc)
         })
     };
@@ -144,52 +233,51 @@ fn multiexp_inner<Q, D, G, S>(
 }
 
 
+#[inline(always)]
+fn multiexp_inner_impl<Q, D, G, S>(
+    algorithm: MultiexpAlgorithm,
+    pool: &Worker,
+    bases: S,
+    density_map: D,
+    exponents: Arc<Vec<<G::Scalar as PrimeField>::Repr>>,
+    skip: u32,
+    c: u32,
+    handle_trivial: bool,
+    hooks: MultiexpHooks
+) -> WorkerFuture< <G as CurveAffine>::Projective, SynthesisError>
+    where for<'a> &'a Q: QueryDensity,
+        D: Send + Sync + 'static + Clone + AsRef<Q>,
+        G: CurveAffine,
+        S: SourceBuilder<G>
+{
+    match algorithm {
+        MultiexpAlgorithm::Buckets => {
+            multiexp_inner(pool, bases, density_map, exponents, skip, c, handle_trivial, hooks)
+        },
+        MultiexpAlgorithm::BucketsPrefetch => {
+            multiexp_inner_with_prefetch(pool, bases, density_map, exponents, skip, c, handle_trivial, hooks)
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
+extern crate prefetch;
+
 cfg_if! {
     if #[cfg(feature = "nightly")] {
         #[inline(always)]
-        fn multiexp_inner_impl<Q, D, G, S>(
-            pool: &Worker,
-            bases: S,
-            density_map: D,
-            exponents: Arc<Vec<<G::Scalar as PrimeField>::Repr>>,
-            skip: u32,
-            c: u32,
-            handle_trivial: bool
-        ) -> WorkerFuture< <G as CurveAffine>::Projective, SynthesisError>
-            where for<'a> &'a Q: QueryDensity,
-                D: Send + Sync + 'static + Clone + AsRef<Q>,
-                G: CurveAffine,
-                S: SourceBuilder<G>
-        {
-            multiexp_inner_with_prefetch(pool, bases, density_map, exponents, skip, c, handle_trivial)
+        fn prefetch_bucket<T>(p: *const T) {
+            use prefetch::prefetch::*;
+
+            prefetch::<Write, High, Data, _>(p);
         }
     } else {
+        // prefetching intrinsics are only available on nightly
         #[inline(always)]
-        fn multiexp_inner_impl<Q, D, G, S>(
-            pool: &Worker,
-            bases: S,
-            density_map: D,
-            exponents: Arc<Vec<<G::Scalar as PrimeField>::Repr>>,
-            skip: u32,
-            c: u32,
-            handle_trivial: bool
-        ) -> WorkerFuture< <G as CurveAffine>::Projective, SynthesisError>
-            where for<'a> &'a Q: QueryDensity,
-                D: Send + Sync + 'static + Clone + AsRef<Q>,
-                G: CurveAffine,
-                S: SourceBuilder<G>
-        {
-            multiexp_inner(pool, bases, density_map, exponents, skip, c, handle_trivial)
-        }
-    }  
+        fn prefetch_bucket<T>(_p: *const T) {}
+    }
 }
 
-
-
-#[cfg(feature = "nightly")]
-extern crate prefetch;
-
-#[cfg(feature = "nightly")]
 fn multiexp_inner_with_prefetch<Q, D, G, S>(
     pool: &Worker,
     bases: S,
@@ -197,18 +285,20 @@ fn multiexp_inner_with_prefetch<Q, D, G, S>(
     exponents: Arc<Vec<<G::Scalar as PrimeField>::Repr>>,
     skip: u32,
     c: u32,
-    handle_trivial: bool
+    handle_trivial: bool,
+    hooks: MultiexpHooks
 ) -> WorkerFuture< <G as CurveAffine>::Projective, SynthesisError>
     where for<'a> &'a Q: QueryDensity,
           D: Send + Sync + 'static + Clone + AsRef<Q>,
           G: CurveAffine,
           S: SourceBuilder<G>
 {
-    use prefetch::prefetch::*;
     // Perform this region of the multiexp
     let this = {
         // This is a Pippenger’s algorithm
         pool.compute(move || {
+            hooks.chunk_started(skip, c);
+
             // Accumulate the result
             let mut acc = G::Projective::zero();
 
@@ -228,9 +318,15 @@ fn multiexp_inner_with_prefetch<Q, D, G, S>(
             let mask = 1 << c;
 
             // Sort the bases into buckets
-            for ((&exp, &next_exp), density) in exponents.iter()
+            for (i, ((&exp, &next_exp), density)) in exponents.iter()
                         .zip(exponents.iter().skip(1).chain(padding.iter()))
-                        .zip(density_map.as_ref().iter()) {
+                        .zip(density_map.as_ref().iter())
+                        .enumerate() {
+                if i % CANCELLATION_CHECK_INTERVAL == 0 {
+                    hooks.check_cancelled()?;
+                }
+
+
                 // no matter what happens - prefetch next bucket
                 if next_exp != zero && next_exp != one {
                     let mut next_exp = next_exp;
@@ -238,7 +334,7 @@ fn multiexp_inner_with_prefetch<Q, D, G, S>(
                     let next_exp = next_exp.as_ref()[0] % mask;
                     if next_exp != 0 {
                         let p: *const <G as CurveAffine>::Projective = &buckets[(next_exp - 1) as usize];
-                        prefetch::<Write, High, Data, _>(p);
+                        prefetch_bucket(p);
                     }
                     
                 }
@@ -281,6 +377,8 @@ fn multiexp_inner_with_prefetch<Q, D, G, S>(
                 acc.add_assign(&running_sum);
             }
 
+            hooks.chunk_finished(skip);
+
             Ok(acc)
         })
     };
@@ -289,7 +387,8 @@ fn multiexp_inner_with_prefetch<Q, D, G, S>(
 }
 
 /// Perform multi-exponentiation. The caller is responsible for ensuring the
-/// query size is the same as the number of exponents.
+/// query size is the same as the number of exponents. Exponents that are not
+/// reduced modulo the group order are reduced first.
 pub fn multiexp<Q, D, G, S>(
     pool: &Worker,
     bases: S,
@@ -301,12 +400,44 @@ pub fn multiexp<Q, D, G, S>(
           G: CurveAffine,
           S: SourceBuilder<G>
 {
-    let c = if exponents.len() < 32 {
-        3u32
-    } else {
-        (f64::from(exponents.len() as u32)).ln().ceil() as u32
+    multiexp_with_hooks(pool, bases, density_map, exponents, MultiexpHooks::default())
+}
+
+/// Same as `multiexp`, but reports progress of every region to the `observer`
+/// and resolves with `SynthesisError::Aborted` soon after `cancellation` is triggered
+pub fn multiexp_with_observer<Q, D, G, S>(
+    pool: &Worker,
+    bases: S,
+    density_map: D,
+    exponents: Arc<Vec<<<G::Engine as ScalarEngine>::Fr as PrimeField>::Repr>>,
+    observer: Arc<dyn MultiexpObserver>,
+    cancellation: CancellationToken
+) -> ChunksJoiner< <G as CurveAffine>::Projective >
+    where for<'a> &'a Q: QueryDensity,
+          D: Send + Sync + 'static + Clone + AsRef<Q>,
+          G: CurveAffine,
+          S: SourceBuilder<G>
+{
+    let hooks = MultiexpHooks {
+        observer: Some(observer),
+        cancellation: Some(cancellation)
     };
 
+    multiexp_with_hooks(pool, bases, density_map, exponents, hooks)
+}
+
+fn multiexp_with_hooks<Q, D, G, S>(
+    pool: &Worker,
+    bases: S,
+    density_map: D,
+    exponents: Arc<Vec<<<G::Engine as ScalarEngine>::Fr as PrimeField>::Repr>>,
+    hooks: MultiexpHooks
+) -> ChunksJoiner< <G as CurveAffine>::Projective >
+    where for<'a> &'a Q: QueryDensity,
+          D: Send + Sync + 'static + Clone + AsRef<Q>,
+          G: CurveAffine,
+          S: SourceBuilder<G>
+{
     if let Some(query_size) = density_map.as_ref().get_query_size() {
         // If the density map has a known query size, it should not be
         // inconsistent with the number of exponents.
@@ -314,163 +445,1000 @@ pub fn multiexp<Q, D, G, S>(
         assert!(query_size == exponents.len());
     }
 
-    let mut skip = 0;
-    let mut futures = Vec::with_capacity((<G::Engine as ScalarEngine>::Fr::NUM_BITS / c + 1) as usize);
+    let c = window_size(exponents.len());
+    let schedule = window_schedule(<G::Engine as ScalarEngine>::Fr::NUM_BITS, c);
+
+    // exponents are inspected by a task on the pool that spawns the regions,
+    // so the caller gets the future back without scanning the exponents itself
+    let worker = pool.clone();
+    let regions_schedule = schedule.clone();
+    let prepare = pool.compute(move || {
+        let exponents = reduce_shared_exponents::<G::Scalar>(&worker, exponents);
+
+        // if there are many zero exponents it's cheaper to drop them once here
+        // than to scan them again in every region
+        let fits_positions = exponents.len() <= u32::max_value() as usize;
+        if fits_positions && is_sparse(count_zero_exponents(&worker, &exponents), exponents.len()) {
+            let positions = compact_exponents(&worker, density_map.as_ref(), &exponents);
+
+            return Ok(spawn_sparse_regions::<G, S>(&worker, &regions_schedule, bases, exponents, Arc::new(positions), hooks));
+        }
+
+        Ok(spawn_regions(MultiexpAlgorithm::default(), &regions_schedule, &worker, bases, density_map, exponents, hooks))
+    });
+
+    ChunksJoiner {
+        state: ChunksState::Preparing(prepare),
+        schedule
+    }
+}
+
+/// Perform multi-exponentiation with the given `algorithm` and window size, which
+/// is chosen based on the number of exponents unless `c_override` is given.
+/// Unlike `multiexp`, exponents are always processed as is, even if most of them are zeroes,
+/// which allows to exercise a particular code path.
+pub fn multiexp_with<Q, D, G, S>(
+    algorithm: MultiexpAlgorithm,
+    c_override: Option<u32>,
+    pool: &Worker,
+    bases: S,
+    density_map: D,
+    exponents: Arc<Vec<<<G::Engine as ScalarEngine>::Fr as PrimeField>::Repr>>
+) -> ChunksJoiner< <G as CurveAffine>::Projective >
+    where for<'a> &'a Q: QueryDensity,
+          D: Send + Sync + 'static + Clone + AsRef<Q>,
+          G: CurveAffine,
+          S: SourceBuilder<G>
+{
+    if let Some(query_size) = density_map.as_ref().get_query_size() {
+        assert!(query_size == exponents.len());
+    }
+
+    let c = c_override.unwrap_or_else(|| window_size(exponents.len()));
+    assert!(c > 0 && c < 32, "window size must be between 1 and 31");
+
+    multiexp_regions(algorithm, c, pool, bases, density_map, exponents, MultiexpHooks::default())
+}
+
+fn multiexp_regions<Q, D, G, S>(
+    algorithm: MultiexpAlgorithm,
+    c: u32,
+    pool: &Worker,
+    bases: S,
+    density_map: D,
+    exponents: Arc<Vec<<<G::Engine as ScalarEngine>::Fr as PrimeField>::Repr>>,
+    hooks: MultiexpHooks
+) -> ChunksJoiner< <G as CurveAffine>::Projective >
+    where for<'a> &'a Q: QueryDensity,
+          D: Send + Sync + 'static + Clone + AsRef<Q>,
+          G: CurveAffine,
+          S: SourceBuilder<G>
+{
+    let schedule = window_schedule(<G::Engine as ScalarEngine>::Fr::NUM_BITS, c);
+
+    let worker = pool.clone();
+    let regions_schedule = schedule.clone();
+    let prepare = pool.compute(move || {
+        let exponents = reduce_shared_exponents::<G::Scalar>(&worker, exponents);
+
+        Ok(spawn_regions(algorithm, &regions_schedule, &worker, bases, density_map, exponents, hooks))
+    });
+
+    ChunksJoiner {
+        state: ChunksState::Preparing(prepare),
+        schedule
+    }
+}
+
+fn spawn_regions<Q, D, G, S>(
+    algorithm: MultiexpAlgorithm,
+    schedule: &[(u32, u32)],
+    pool: &Worker,
+    bases: S,
+    density_map: D,
+    exponents: Arc<Vec<<<G::Engine as ScalarEngine>::Fr as PrimeField>::Repr>>,
+    hooks: MultiexpHooks
+) -> RegionsJoin< <G as CurveAffine>::Projective >
+    where for<'a> &'a Q: QueryDensity,
+          D: Send + Sync + 'static + Clone + AsRef<Q>,
+          G: CurveAffine,
+          S: SourceBuilder<G>
+{
+    let mut futures = Vec::with_capacity(schedule.len());
 
-    while skip < <G::Engine as ScalarEngine>::Fr::NUM_BITS {
+    for &(skip, width) in schedule.iter() {
         let chunk_future = if skip == 0 {
-            multiexp_inner_impl(pool, bases.clone(), density_map.clone(), exponents.clone(), 0, c, true)
+            multiexp_inner_impl(algorithm, pool, bases.clone(), density_map.clone(), exponents.clone(), 0, width, true, hooks.clone())
         } else {
-            multiexp_inner_impl(pool, bases.clone(), density_map.clone(), exponents.clone(), skip, c, false)
+            multiexp_inner_impl(algorithm, pool, bases.clone(), density_map.clone(), exponents.clone(), skip, width, false, hooks.clone())
         };
 
         futures.push(chunk_future);
-        skip += c;
     }
 
-    let join = join_all(futures);
-
-    ChunksJoiner {
-        join,
-        c
-    } 
+    // the first failed region (e.g. aborted one) resolves the whole join
+    // without waiting for the rest of the regions
+    try_join_all(futures)
 }
 
-pub struct ChunksJoiner<G: CurveProjective> {
-    join: JoinAll< WorkerFuture<G, SynthesisError> >,
-    c: u32
+/// Splits `num_bits` into regions of `c` bits starting from the lowest ones. Returns
+/// `(skip, width)` for every region, where only the highest one may be narrower than `c`
+pub fn window_schedule(num_bits: u32, c: u32) -> Vec<(u32, u32)> {
+    assert!(c > 0);
+
+    let mut schedule = Vec::with_capacity((num_bits / c + 1) as usize);
+    let mut skip = 0;
+
+    while skip < num_bits {
+        schedule.push((skip, std::cmp::min(c, num_bits - skip)));
+        skip += c;
+    }
+
+    schedule
 }
 
-impl<G: CurveProjective> Future for ChunksJoiner<G> {
-    type Output = Result<G, SynthesisError>;
+/// Regions only look at the lowest `NUM_BITS` of every exponent, so exponents that
+/// are not reduced modulo the group order are reduced before running the regions. Returns
+/// `None` if all the exponents are reduced, which is the case for representations of field elements
+fn reduce_exponents<F: PrimeField>(pool: &Worker, exponents: &[F::Repr]) -> Option<Vec<F::Repr>> {
+    let modulus = F::char();
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output>
-    {
-        let c = self.as_ref().c;
-        let join = unsafe { self.map_unchecked_mut(|s| &mut s.join) };
-        match join.poll(cx) {
-            Poll::Ready(v) => {
-                let v = join_chunks(v, c);
-                return Poll::Ready(v);
-            },
-            Poll::Pending => {
-                return Poll::Pending;
-            }
-        }
+    let all_reduced = map_chunks(pool, exponents.len(), |range| {
+        exponents[range].iter().all(|e| *e < modulus)
+    }).into_iter().all(|reduced| reduced);
+
+    if all_reduced {
+        return None;
     }
+
+    let mut reduced = exponents.to_vec();
+
+    pool.scope(reduced.len(), |scope, chunk| {
+        for exps in reduced.chunks_mut(chunk) {
+            scope.spawn(move |_| {
+                for exp in exps.iter_mut() {
+                    *exp = reduce_repr::<F>(*exp);
+                }
+            });
+        }
+    });
+
+    Some(reduced)
 }
 
-impl<G: CurveProjective> ChunksJoiner<G> {
-    pub fn wait(self) -> <Self as Future>::Output {
-        block_on(self)
+/// Same as `reduce_exponents`, but keeps the original vector if all the exponents are reduced
+fn reduce_shared_exponents<F: PrimeField>(pool: &Worker, exponents: Arc<Vec<F::Repr>>) -> Arc<Vec<F::Repr>> {
+    match reduce_exponents::<F>(pool, &exponents) {
+        Some(reduced) => Arc::new(reduced),
+        None => exponents
     }
 }
 
-fn join_chunks<G: CurveProjective>
-    (chunks: Vec<Result<G, SynthesisError>>, c: u32) -> Result<G, SynthesisError> {
-    if chunks.len() == 0 {
-        return Ok(G::zero());
+/// Computes `repr mod F::char()` bit by bit, starting from the highest bit. The modulus
+/// is assumed to leave at least one spare bit, so doubling the remainder can not overflow
+fn reduce_repr<F: PrimeField>(repr: F::Repr) -> F::Repr {
+    let modulus = F::char();
+
+    if repr < modulus {
+        return repr;
     }
 
-    let mut iter = chunks.into_iter().rev();
-    let higher = iter.next().expect("is some chunk result");
-    let mut higher = higher?;
+    let mut remainder = F::Repr::default();
 
-    for chunk in iter {
-        let this = chunk?;
-        for _ in 0..c {
-            higher.double();
-        }
+    for i in (0..repr.num_bits()).rev() {
+        remainder.mul2();
+        remainder.as_mut()[0] |= (repr.as_ref()[(i / 64) as usize] >> (i % 64)) & 1;
 
-        higher.add_assign(&this);
+        if remainder >= modulus {
+            remainder.sub_noborrow(&modulus);
+        }
     }
 
-    Ok(higher)
+    remainder
 }
 
+/// Exponents are compacted before running the regions if at least
+/// `1 / SPARSE_ZEROS_FRACTION_INVERSE` of them are zeroes
+const SPARSE_ZEROS_FRACTION_INVERSE: usize = 4;
 
-/// Perform multi-exponentiation. The caller is responsible for ensuring that
-/// the number of bases is the same as the number of exponents.
-#[allow(dead_code)]
-pub fn dense_multiexp<G: CurveAffine>(
-    pool: &Worker,
-    bases: & [G],
-    exponents: & [<<G::Engine as ScalarEngine>::Fr as PrimeField>::Repr]
-) -> Result<<G as CurveAffine>::Projective, SynthesisError>
-{
-    if exponents.len() != bases.len() {
-        return Err(SynthesisError::AssignmentMissing);
-    }
-    let c = if exponents.len() < 32 {
+fn window_size(num_exponents: usize) -> u32 {
+    if num_exponents < 32 {
         3u32
     } else {
-        (f64::from(exponents.len() as u32)).ln().ceil() as u32
-    };
+        (f64::from(num_exponents as u32)).ln().ceil() as u32
+    }
+}
 
-    dense_multiexp_inner(pool, bases, exponents, 0, c, true)
+fn is_sparse(num_zeros: usize, num_exponents: usize) -> bool {
+    num_zeros != 0 && num_zeros * SPARSE_ZEROS_FRACTION_INVERSE >= num_exponents
 }
 
-fn dense_multiexp_inner<G: CurveAffine>(
-    pool: &Worker,
-    bases: & [G],
-    exponents: & [<<G::Engine as ScalarEngine>::Fr as PrimeField>::Repr],
-    mut skip: u32,
-    c: u32,
-    handle_trivial: bool
-) -> Result<<G as CurveAffine>::Projective, SynthesisError>
-{   
-    use std::sync::{Mutex};
-    // Perform this region of the multiexp. We use a different strategy - go over region in parallel,
-    // then over another region, etc. No Arc required
-    let this = {
-        // let mask = (1u64 << c) - 1u64;
-        let this_region = Mutex::new(<G as CurveAffine>::Projective::zero());
-        let arc = Arc::new(this_region);
-        pool.scope(bases.len(), |scope, chunk| {
-            for (base, exp) in bases.chunks(chunk).zip(exponents.chunks(chunk)) {
-                let this_region_rwlock = arc.clone();
-                // let handle = 
-                scope.spawn(move |_| {
-                    let mut buckets = vec![<G as CurveAffine>::Projective::zero(); (1 << c) - 1];
-                    // Accumulate the result
-                    let mut acc = G::Projective::zero();
-                    let zero = <G::Engine as ScalarEngine>::Fr::zero().into_repr();
-                    let one = <G::Engine as ScalarEngine>::Fr::one().into_repr();
+fn count_zero_exponents<R: PrimeFieldRepr>(pool: &Worker, exponents: &[R]) -> usize {
+    map_chunks(pool, exponents.len(), |range| {
+        exponents[range].iter().filter(|e| e.is_zero()).count()
+    }).into_iter().sum()
+}
 
-                    for (base, &exp) in base.iter().zip(exp.iter()) {
-                        // let index = (exp.as_ref()[0] & mask) as usize;
+/// Returns the nonzero exponents together with their indexes, in the original order
+fn collect_nonzero_exponents<R: PrimeFieldRepr>(pool: &Worker, exponents: &[R]) -> Vec<(usize, R)> {
+    let parts = map_chunks(pool, exponents.len(), |range| {
+        let offset = range.start;
 
-                        // if index != 0 {
-                        //     buckets[index - 1].add_assign_mixed(base);
-                        // }
+        exponents[range].iter().enumerate()
+            .filter(|(_, e)| !e.is_zero())
+            .map(|(i, e)| (offset + i, *e))
+            .collect::<Vec<_>>()
+    });
 
-                        // exp.shr(c as u32);
+    concat_parts(parts)
+}
 
-                        if exp != zero {
-                            if exp == one {
-                                if handle_trivial {
-                                    acc.add_assign_mixed(base);
-                                }
-                            } else {
-                                let mut exp = exp;
-                                exp.shr(skip);
-                                let exp = exp.as_ref()[0] % (1 << c);
-                                if exp != 0 {
-                                    buckets[(exp - 1) as usize].add_assign_mixed(base);
-                                }
-                            }
-                        }
-                    }
+/// Finds the exponents that are nonzero and have a base according to the `density_map`.
+/// Returns the index of every such exponent paired with the position of its base in the
+/// source. Exponents themselves are not copied, as the prover runs several multiexps
+/// over the same exponents, so only 8 bytes per nonzero exponent are allocated
+fn compact_exponents<Q, R>(
+    pool: &Worker,
+    density_map: &Q,
+    exponents: &[R]
+) -> Vec<(u32, u32)>
+    where for<'a> &'a Q: QueryDensity,
+          R: PrimeFieldRepr
+{
+    assert!(exponents.len() <= u32::max_value() as usize, "positions must fit into u32");
+
+    // density is only available as an iterator, so it's packed once into
+    // a bitmap to let every chunk look at its own part of it
+    let density = pack_density(density_map, exponents.len());
+    let is_dense = |i: usize| density[i / 64] & (1 << (i % 64)) != 0;
+
+    // number of bases in every chunk gives the position of
+    // the first base of every chunk in the source
+    let num_bases = map_chunks(pool, exponents.len(), |range| {
+        (range.start, range.filter(|&i| is_dense(i)).count())
+    });
+
+    let mut base_position = 0;
+    let offsets = num_bases.into_iter().map(|(start, num_bases)| {
+        let offset = (start, base_position);
+        base_position += num_bases;
+
+        offset
+    }).collect::<Vec<_>>();
+
+    // chunks are the same as in the previous pass
+    let parts = map_chunks(pool, exponents.len(), |range| {
+        let idx = offsets.binary_search_by_key(&range.start, |&(start, _)| start).expect("chunks must not change");
+        let mut base_position = offsets[idx].1;
+        let mut part = vec![];
+
+        for i in range {
+            if is_dense(i) {
+                if !exponents[i].is_zero() {
+                    part.push((i as u32, base_position as u32));
+                }
+                base_position += 1;
+            }
+        }
 
-                    // buckets are filled with the corresponding accumulated value, now sum
-                    let mut running_sum = G::Projective::zero();
-                    for exp in buckets.into_iter().rev() {
-                        running_sum.add_assign(&exp);
-                        acc.add_assign(&running_sum);
-                    }
+        part
+    });
 
-                    let mut guard = match this_region_rwlock.lock() {
-                        Ok(guard) => guard,
+    concat_parts(parts)
+}
+
+/// Packs the first `len` values of the `density_map` into a bitmap. Values missing
+/// from a shorter density map are unset
+fn pack_density<Q>(density_map: &Q, len: usize) -> Vec<u64>
+    where for<'a> &'a Q: QueryDensity
+{
+    let mut density = vec![0u64; (len + 63) / 64];
+    for (i, d) in density_map.iter().take(len).enumerate() {
+        if d {
+            density[i / 64] |= 1 << (i % 64);
+        }
+    }
+
+    density
+}
+
+/// Calls `f` for the ranges of indexes `0..len` split into chunks by the `pool`
+/// and returns the results in the order of the ranges
+fn map_chunks<T, F>(pool: &Worker, len: usize, f: F) -> Vec<T>
+    where T: Send,
+          F: Fn(Range<usize>) -> T + Sync
+{
+    let results = Mutex::new(vec![]);
+
+    pool.scope(len, |scope, chunk| {
+        for start in (0..len).step_by(chunk) {
+            let results = &results;
+            let f = &f;
+            scope.spawn(move |_| {
+                let result = f(start..std::cmp::min(start + chunk, len));
+
+                results.lock().expect("must not be poisoned").push((start, result));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().expect("must not be poisoned");
+    results.sort_by_key(|&(start, _)| start);
+
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+fn concat_parts<T>(parts: Vec<Vec<T>>) -> Vec<T> {
+    let mut result = Vec::with_capacity(parts.iter().map(|p| p.len()).sum());
+    for part in parts.into_iter() {
+        result.extend(part);
+    }
+
+    result
+}
+
+fn spawn_sparse_regions<G, S>(
+    pool: &Worker,
+    schedule: &[(u32, u32)],
+    bases: S,
+    exponents: Arc<Vec<<G::Scalar as PrimeField>::Repr>>,
+    positions: Arc<Vec<(u32, u32)>>,
+    hooks: MultiexpHooks
+) -> RegionsJoin< <G as CurveAffine>::Projective >
+    where G: CurveAffine,
+          S: SourceBuilder<G>
+{
+    debug_assert!(exponents.iter().all(|e| *e < G::Scalar::char()), "exponents must be representations of field elements");
+
+    let mut futures = Vec::with_capacity(schedule.len());
+
+    for &(skip, width) in schedule.iter() {
+        let chunk_future = multiexp_inner_sparse(pool, bases.clone(), exponents.clone(), positions.clone(), skip, width, skip == 0, hooks.clone());
+
+        futures.push(chunk_future);
+    }
+
+    try_join_all(futures)
+}
+
+/// Same as `multiexp_inner`, but only visits the exponents listed in `positions`
+/// together with the positions of their bases, so only the bases with nonzero
+/// exponents are touched
+fn multiexp_inner_sparse<G, S>(
+    pool: &Worker,
+    bases: S,
+    exponents: Arc<Vec<<G::Scalar as PrimeField>::Repr>>,
+    positions: Arc<Vec<(u32, u32)>>,
+    skip: u32,
+    c: u32,
+    handle_trivial: bool,
+    hooks: MultiexpHooks
+) -> WorkerFuture< <G as CurveAffine>::Projective, SynthesisError>
+    where G: CurveAffine,
+          S: SourceBuilder<G>
+{
+    pool.compute(move || {
+        hooks.chunk_started(skip, c);
+
+        let mut acc = G::Projective::zero();
+
+        let mut bases = bases.new();
+
+        let mut buckets = vec![<G as CurveAffine>::Projective::zero(); (1 << c) - 1];
+
+        let one = <G::Engine as ScalarEngine>::Fr::one().into_repr();
+
+        // position of the next base in the source
+        let mut position = 0;
+
+        for (i, &(index, base_position)) in positions.iter().enumerate() {
+            if i % CANCELLATION_CHECK_INTERVAL == 0 {
+                hooks.check_cancelled()?;
+            }
+
+            let exp = exponents[index as usize];
+            let base_position = base_position as usize;
+
+            if base_position != position {
+                bases.skip(base_position - position)?;
+            }
+            position = base_position + 1;
+
+            if exp == one {
+                if handle_trivial {
+                    bases.add_assign_mixed(&mut acc)?;
+                } else {
+                    bases.skip(1)?;
+                }
+            } else {
+                let mut exp = exp;
+                exp.shr(skip);
+                let exp = exp.as_ref()[0] % (1 << c);
+
+                if exp != 0 {
+                    bases.add_assign_mixed(&mut buckets[(exp - 1) as usize])?;
+                } else {
+                    bases.skip(1)?;
+                }
+            }
+        }
+
+        let mut running_sum = G::Projective::zero();
+        for exp in buckets.into_iter().rev() {
+            running_sum.add_assign(&exp);
+            acc.add_assign(&running_sum);
+        }
+
+        hooks.chunk_finished(skip);
+
+        Ok(acc)
+    })
+}
+
+type RegionsJoin<G> = TryJoinAll< WorkerFuture<G, SynthesisError> >;
+
+enum ChunksState<G: CurveProjective> {
+    /// A task on the pool is still inspecting the exponents and will
+    /// resolve with the regions it has spawned
+    Preparing(WorkerFuture<RegionsJoin<G>, SynthesisError>),
+    Running(RegionsJoin<G>),
+    /// Every task computes a group of adjacent regions
+    Groups(TryJoinAll< WorkerFuture<Vec<G>, SynthesisError> >)
+}
+
+pub struct ChunksJoiner<G: CurveProjective> {
+    state: ChunksState<G>,
+    schedule: Vec<(u32, u32)>
+}
+
+impl<G: CurveProjective> Future for ChunksJoiner<G> {
+    type Output = Result<G, SynthesisError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output>
+    {
+        let this = unsafe { self.get_unchecked_mut() };
+        match this.poll_parts(cx) {
+            Poll::Ready(v) => {
+                let v = v.map(|chunks| join_chunks_by_schedule(&chunks, &this.schedule));
+                return Poll::Ready(v);
+            },
+            Poll::Pending => {
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+impl<G: CurveProjective> ChunksJoiner<G> {
+    fn poll_parts(&mut self, cx: &mut Context) -> Poll<Result<Vec<G>, SynthesisError>> {
+        loop {
+            let join = match self.state {
+                ChunksState::Preparing(ref mut prepare) => {
+                    let prepare = unsafe { Pin::new_unchecked(prepare) };
+                    match prepare.poll(cx) {
+                        Poll::Ready(Ok(join)) => join,
+                        Poll::Ready(Err(e)) => {
+                            return Poll::Ready(Err(e));
+                        },
+                        Poll::Pending => {
+                            return Poll::Pending;
+                        }
+                    }
+                },
+                ChunksState::Running(ref mut join) => {
+                    let join = unsafe { Pin::new_unchecked(join) };
+                    return join.poll(cx);
+                },
+                ChunksState::Groups(ref mut join) => {
+                    let join = unsafe { Pin::new_unchecked(join) };
+                    return join.poll(cx).map(|groups| {
+                        groups.map(|groups| groups.into_iter().flatten().collect())
+                    });
+                }
+            };
+
+            self.state = ChunksState::Running(join);
+        }
+    }
+
+    pub fn wait(self) -> <Self as Future>::Output {
+        block_on(self)
+    }
+
+    /// Window size `c` used by every region of this multiexp, except
+    /// for the highest one that may be narrower
+    pub fn window_size(&self) -> u32 {
+        self.schedule[0].1
+    }
+
+    /// `(skip, width)` of every region of this multiexp, starting from the lowest bits
+    pub fn window_schedule(&self) -> &[(u32, u32)] {
+        &self.schedule
+    }
+
+    /// Waits for all the regions and returns the joined result together with
+    /// the per-region results, ordered starting from the lowest bits. Parts can be
+    /// joined again with `join_chunks_by_schedule` or with `join_chunks_from_parts`
+    /// using the same window size.
+    pub fn wait_with_parts(mut self) -> Result<(G, Vec<G>), SynthesisError> {
+        let parts = block_on(poll_fn(|cx| self.poll_parts(cx)))?;
+        let joined = join_chunks_by_schedule(&parts, &self.schedule);
+
+        Ok((joined, parts))
+    }
+}
+
+/// Joins the per-region results of a multiexp with window size `c`, where
+/// `parts[i]` is the result for the bits from `i * c` to `(i + 1) * c`
+pub fn join_chunks_from_parts<G: CurveProjective>
+    (parts: &[G], c: u32) -> G {
+    if parts.len() == 0 {
+        return G::zero();
+    }
+
+    let mut iter = parts.iter().rev();
+    let mut higher = *iter.next().expect("is some chunk result");
+
+    for this in iter {
+        for _ in 0..c {
+            higher.double();
+        }
+
+        higher.add_assign(this);
+    }
+
+    higher
+}
+
+/// Joins the per-region results of a multiexp, where `parts[i]` is the result
+/// for the region `schedule[i]` given as `(skip, width)`
+pub fn join_chunks_by_schedule<G: CurveProjective>
+    (parts: &[G], schedule: &[(u32, u32)]) -> G {
+    assert_eq!(parts.len(), schedule.len());
+
+    if parts.len() == 0 {
+        return G::zero();
+    }
+
+    let mut higher = parts[parts.len() - 1];
+
+    for i in (0..(parts.len() - 1)).rev() {
+        let (skip, width) = schedule[i];
+        debug_assert_eq!(skip + width, schedule[i + 1].0, "regions must be adjacent");
+
+        // every lower region is shifted by its own width
+        for _ in 0..width {
+            higher.double();
+        }
+
+        higher.add_assign(&parts[i]);
+    }
+
+    higher
+}
+
+
+/// Number of scalars that `multiexp_from_field_elements` converts into
+/// representations at once, which bounds the size of its scratch buffers
+const FIELD_ELEMENTS_BLOCK_SIZE: usize = 1 << 14;
+
+/// Perform multi-exponentiation over scalars given as field elements, so the caller
+/// doesn't have to allocate a copy of the full scalar vector just to get an `Arc<Vec<Repr>>`.
+/// Regions are split into about one group per thread, and every group runs as a single
+/// task that converts the scalars block by block into its own reused scratch buffer.
+/// The caller is responsible for ensuring the query size is the same as the number of scalars.
+pub fn multiexp_from_field_elements<Q, D, G, S, T>(
+    pool: &Worker,
+    bases: S,
+    density_map: D,
+    scalars: Arc<T>
+) -> ChunksJoiner< <G as CurveAffine>::Projective >
+    where for<'a> &'a Q: QueryDensity,
+          D: Send + Sync + 'static + Clone + AsRef<Q>,
+          G: CurveAffine,
+          S: SourceBuilder<G>,
+          T: AsRef<[G::Scalar]> + Send + Sync + 'static
+{
+    multiexp_from_field_elements_with_block_size(pool, bases, density_map, scalars, FIELD_ELEMENTS_BLOCK_SIZE)
+}
+
+fn multiexp_from_field_elements_with_block_size<Q, D, G, S, T>(
+    pool: &Worker,
+    bases: S,
+    density_map: D,
+    scalars: Arc<T>,
+    block_size: usize
+) -> ChunksJoiner< <G as CurveAffine>::Projective >
+    where for<'a> &'a Q: QueryDensity,
+          D: Send + Sync + 'static + Clone + AsRef<Q>,
+          G: CurveAffine,
+          S: SourceBuilder<G>,
+          T: AsRef<[G::Scalar]> + Send + Sync + 'static
+{
+    let num_scalars = AsRef::<[G::Scalar]>::as_ref(&*scalars).len();
+
+    let c = window_size(num_scalars);
+
+    if let Some(query_size) = density_map.as_ref().get_query_size() {
+        assert!(query_size == num_scalars);
+    }
+
+    let schedule = window_schedule(G::Scalar::NUM_BITS, c);
+
+    // every group converts the scalars once, so there are no more
+    // groups than threads, and all of them can run at the same time
+    let num_groups = std::cmp::min(1 << pool.log_num_cpus(), schedule.len());
+    let group_size = (schedule.len() + num_groups - 1) / num_groups;
+
+    let futures = schedule.chunks(group_size).map(|group| {
+        multiexp_group_from_field_elements(
+            pool,
+            bases.clone(),
+            density_map.clone(),
+            scalars.clone(),
+            group.to_vec(),
+            block_size
+        )
+    }).collect::<Vec<_>>();
+
+    ChunksJoiner {
+        state: ChunksState::Groups(try_join_all(futures)),
+        schedule
+    }
+}
+
+/// Accumulates all the regions of the `group` in a single task, resolves
+/// with the results of the regions in the same order
+fn multiexp_group_from_field_elements<Q, D, G, S, T>(
+    pool: &Worker,
+    bases: S,
+    density_map: D,
+    scalars: Arc<T>,
+    group: Vec<(u32, u32)>,
+    block_size: usize
+) -> WorkerFuture< Vec<<G as CurveAffine>::Projective>, SynthesisError>
+    where for<'a> &'a Q: QueryDensity,
+          D: Send + Sync + 'static + Clone + AsRef<Q>,
+          G: CurveAffine,
+          S: SourceBuilder<G>,
+          T: AsRef<[G::Scalar]> + Send + Sync + 'static
+{
+    pool.compute(move || {
+        let scalars = AsRef::<[G::Scalar]>::as_ref(&*scalars);
+
+        let mut regions = group.iter().map(|&(skip, width)| {
+            FieldElementsRegion::<G, S::Source>::new(bases.clone().new(), skip, width)
+        }).collect::<Vec<_>>();
+
+        let mut density = density_map.as_ref().iter();
+        let mut block_density = Vec::with_capacity(block_size);
+        let mut scratch = Vec::with_capacity(std::cmp::min(block_size, scalars.len()));
+
+        for block in scalars.chunks(block_size) {
+            block_density.clear();
+            block_density.extend(density.by_ref().take(block.len()));
+
+            scratch.clear();
+            scratch.extend(block.iter().map(|s| s.into_repr()));
+
+            for region in regions.iter_mut() {
+                region.accumulate(&scratch, &block_density)?;
+            }
+        }
+
+        Ok(regions.into_iter().map(|r| r.finish()).collect())
+    })
+}
+
+/// State of a single region of `multiexp_from_field_elements` that
+/// is carried over from one block of scalars to the next
+struct FieldElementsRegion<G: CurveAffine, B: Source<G>> {
+    bases: B,
+    skip: u32,
+    c: u32,
+    acc: G::Projective,
+    buckets: Vec<G::Projective>
+}
+
+impl<G: CurveAffine, B: Source<G>> FieldElementsRegion<G, B> {
+    fn new(bases: B, skip: u32, c: u32) -> Self {
+        Self {
+            bases,
+            skip,
+            c,
+            acc: G::Projective::zero(),
+            buckets: vec![G::Projective::zero(); (1 << c) - 1]
+        }
+    }
+
+    fn accumulate(
+        &mut self,
+        exps: &[<G::Scalar as PrimeField>::Repr],
+        density: &[bool]
+    ) -> Result<(), SynthesisError>
+    {
+        let zero = G::Scalar::zero().into_repr();
+        let one = G::Scalar::one().into_repr();
+        let handle_trivial = self.skip == 0;
+
+        for (&exp, &density) in exps.iter().zip(density.iter()) {
+            if density {
+                if exp == zero {
+                    self.bases.skip(1)?;
+                } else if exp == one {
+                    if handle_trivial {
+                        self.bases.add_assign_mixed(&mut self.acc)?;
+                    } else {
+                        self.bases.skip(1)?;
+                    }
+                } else {
+                    let mut exp = exp;
+                    exp.shr(self.skip);
+                    let exp = exp.as_ref()[0] % (1 << self.c);
+
+                    if exp != 0 {
+                        self.bases.add_assign_mixed(&mut self.buckets[(exp - 1) as usize])?;
+                    } else {
+                        self.bases.skip(1)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) -> G::Projective {
+        let mut acc = self.acc;
+
+        let mut running_sum = G::Projective::zero();
+        for exp in self.buckets.into_iter().rev() {
+            running_sum.add_assign(&exp);
+            acc.add_assign(&running_sum);
+        }
+
+        acc
+    }
+}
+
+/// Perform multi-exponentiation using signed digits. Every scalar is first recoded
+/// into windows of `c` bits with digits in `[-2^(c-1), 2^(c-1)]`, so every window
+/// needs only `2^(c-1)` buckets, and negative digits subtract the base, which is as
+/// cheap as adding it. The caller is responsible for ensuring that the number of bases
+/// is the same as the number of exponents.
+pub fn multiexp_signed<G, S>(
+    pool: &Worker,
+    bases: S,
+    exponents: Arc<Vec<<G::Scalar as PrimeField>::Repr>>
+) -> ChunksJoiner< <G as CurveAffine>::Projective >
+    where G: CurveAffine,
+          S: SourceBuilder<G>
+{
+    let c = window_size(exponents.len());
+
+    // the highest window is never recoded, so one extra window
+    // is enough to absorb the carry from the window below it
+    let num_windows = (G::Scalar::NUM_BITS / c + 1) as usize;
+
+    // bits above the highest window are never looked at
+    let exponents = reduce_shared_exponents::<G::Scalar>(pool, exponents);
+
+    let digits = Arc::new(recode_into_signed_digits(pool, &exponents, c, num_windows));
+
+    let mut futures = Vec::with_capacity(num_windows);
+    for window in 0..num_windows {
+        futures.push(multiexp_signed_inner(pool, bases.clone(), digits.clone(), window, num_windows, c));
+    }
+
+    let join = try_join_all(futures);
+
+    // all windows have the same width, including the extra one
+    let schedule = (0..num_windows).map(|w| (w as u32 * c, c)).collect();
+
+    ChunksJoiner {
+        state: ChunksState::Running(join),
+        schedule
+    }
+}
+
+fn multiexp_signed_inner<G, S>(
+    pool: &Worker,
+    bases: S,
+    digits: Arc<Vec<i32>>,
+    window: usize,
+    num_windows: usize,
+    c: u32
+) -> WorkerFuture< <G as CurveAffine>::Projective, SynthesisError>
+    where G: CurveAffine,
+          S: SourceBuilder<G>
+{
+    pool.compute(move || {
+        let mut acc = G::Projective::zero();
+        let mut bases = bases.new();
+
+        // buckets for absolute values of digits from 1 to 2^(c-1)
+        let mut buckets = vec![<G as CurveAffine>::Projective::zero(); 1 << (c - 1)];
+
+        for digits in digits.chunks(num_windows) {
+            let digit = digits[window];
+            if digit > 0 {
+                bases.add_assign_mixed(&mut buckets[(digit - 1) as usize])?;
+            } else if digit < 0 {
+                // B - P = -((-B) + P)
+                let bucket = &mut buckets[(-digit - 1) as usize];
+                bucket.negate();
+                bases.add_assign_mixed(bucket)?;
+                bucket.negate();
+            } else {
+                bases.skip(1)?;
+            }
+        }
+
+        let mut running_sum = G::Projective::zero();
+        for exp in buckets.into_iter().rev() {
+            running_sum.add_assign(&exp);
+            acc.add_assign(&running_sum);
+        }
+
+        Ok(acc)
+    })
+}
+
+/// Recodes every exponent into `num_windows` signed digits, stored as a matrix
+/// with one row of digits per exponent
+fn recode_into_signed_digits<R: PrimeFieldRepr>(
+    pool: &Worker,
+    exponents: &[R],
+    c: u32,
+    num_windows: usize
+) -> Vec<i32>
+{
+    let mut digits = vec![0i32; exponents.len() * num_windows];
+
+    pool.scope(exponents.len(), |scope, chunk| {
+        for (exps, digits) in exponents.chunks(chunk).zip(digits.chunks_mut(chunk * num_windows)) {
+            scope.spawn(move |_| {
+                for (exp, digits) in exps.iter().zip(digits.chunks_mut(num_windows)) {
+                    make_signed_digits(exp.as_ref(), c, digits);
+                }
+            });
+        }
+    });
+
+    digits
+}
+
+fn make_signed_digits(repr: &[u64], c: u32, digits: &mut [i32]) {
+    let half = 1i64 << (c - 1);
+    let full = 1i64 << c;
+    let last = digits.len() - 1;
+
+    let mut carry = 0i64;
+    for (window, digit) in digits.iter_mut().enumerate() {
+        let mut d = get_window_bits(repr, (window as u32) * c, c) as i64 + carry;
+        carry = 0;
+        if window != last && d > half {
+            d -= full;
+            carry = 1;
+        }
+
+        *digit = d as i32;
+    }
+}
+
+fn get_window_bits(repr: &[u64], skip: u32, c: u32) -> u64 {
+    let limb = (skip / 64) as usize;
+    let shift = skip % 64;
+
+    if limb >= repr.len() {
+        return 0;
+    }
+
+    let mut bits = repr[limb] >> shift;
+    if shift + c > 64 && limb + 1 < repr.len() {
+        bits |= repr[limb + 1] << (64 - shift);
+    }
+
+    bits & ((1u64 << c) - 1)
+}
+
+/// Perform multi-exponentiation. The caller is responsible for ensuring that
+/// the number of bases is the same as the number of exponents. Exponents that
+/// are not reduced modulo the group order are reduced first.
+#[allow(dead_code)]
+pub fn dense_multiexp<G: CurveAffine>(
+    pool: &Worker,
+    bases: & [G],
+    exponents: & [<<G::Engine as ScalarEngine>::Fr as PrimeField>::Repr]
+) -> Result<<G as CurveAffine>::Projective, SynthesisError>
+{
+    if exponents.len() != bases.len() {
+        return Err(SynthesisError::AssignmentMissing);
+    }
+
+    let reduced = reduce_exponents::<G::Scalar>(pool, exponents);
+    let exponents = reduced.as_ref().map(|e| &e[..]).unwrap_or(exponents);
+
+    if is_sparse(count_zero_exponents(pool, exponents), exponents.len()) {
+        let (bases, exponents): (Vec<G>, Vec<_>) = collect_nonzero_exponents(pool, exponents)
+            .into_iter()
+            .map(|(i, exp)| (bases[i], exp))
+            .unzip();
+
+        let c = window_size(exponents.len());
+
+        return dense_multiexp_inner(pool, &bases, &exponents, 0, c, true);
+    }
+
+    let c = window_size(exponents.len());
+
+    dense_multiexp_inner(pool, bases, exponents, 0, c, true)
+}
+
+fn dense_multiexp_inner<G: CurveAffine>(
+    pool: &Worker,
+    bases: & [G],
+    exponents: & [<<G::Engine as ScalarEngine>::Fr as PrimeField>::Repr],
+    mut skip: u32,
+    c: u32,
+    handle_trivial: bool
+) -> Result<<G as CurveAffine>::Projective, SynthesisError>
+{   
+    use std::sync::{Mutex};
+    // Perform this region of the multiexp. We use a different strategy - go over region in parallel,
+    // then over another region, etc. No Arc required
+    // the highest region may be narrower than the others
+    let width = std::cmp::min(c, <G::Engine as ScalarEngine>::Fr::NUM_BITS - skip);
+
+    let this = {
+        // let mask = (1u64 << c) - 1u64;
+        let this_region = Mutex::new(<G as CurveAffine>::Projective::zero());
+        let arc = Arc::new(this_region);
+        pool.scope(bases.len(), |scope, chunk| {
+            for (base, exp) in bases.chunks(chunk).zip(exponents.chunks(chunk)) {
+                let this_region_rwlock = arc.clone();
+                // let handle = 
+                scope.spawn(move |_| {
+                    let mut buckets = vec![<G as CurveAffine>::Projective::zero(); (1 << width) - 1];
+                    // Accumulate the result
+                    let mut acc = G::Projective::zero();
+                    let zero = <G::Engine as ScalarEngine>::Fr::zero().into_repr();
+                    let one = <G::Engine as ScalarEngine>::Fr::one().into_repr();
+
+                    for (base, &exp) in base.iter().zip(exp.iter()) {
+                        // let index = (exp.as_ref()[0] & mask) as usize;
+
+                        // if index != 0 {
+                        //     buckets[index - 1].add_assign_mixed(base);
+                        // }
+
+                        // exp.shr(c as u32);
+
+                        if exp != zero {
+                            if exp == one {
+                                if handle_trivial {
+                                    acc.add_assign_mixed(base);
+                                }
+                            } else {
+                                let mut exp = exp;
+                                exp.shr(skip);
+                                let exp = exp.as_ref()[0] % (1 << width);
+                                if exp != 0 {
+                                    buckets[(exp - 1) as usize].add_assign_mixed(base);
+                                }
+                            }
+                        }
+                    }
+
+                    // buckets are filled with the corresponding accumulated value, now sum
+                    let mut running_sum = G::Projective::zero();
+                    for exp in buckets.into_iter().rev() {
+                        running_sum.add_assign(&exp);
+                        acc.add_assign(&running_sum);
+                    }
+
+                    let mut guard = match this_region_rwlock.lock() {
+                        Ok(guard) => guard,
                         Err(_) => {
                             panic!("poisoned!"); 
                             // poisoned.into_inner()
@@ -632,4 +1600,542 @@ fn test_dense_multiexp_vs_new_multiexp() {
     println!("{} ns for sparse for {} samples", duration_ns, SAMPLES);
 
     assert_eq!(dense, sparse);
-}
\ No newline at end of file
+}
+#[test]
+fn test_multiexp_with_observer_matches_multiexp() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::bn256::Bn256;
+    use std::sync::atomic::AtomicUsize;
+
+    struct CountingObserver {
+        started: AtomicUsize,
+        finished: AtomicUsize
+    }
+
+    impl MultiexpObserver for CountingObserver {
+        fn on_chunk_started(&self, _skip: u32, _c: u32) {
+            self.started.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_chunk_finished(&self, _skip: u32) {
+            self.finished.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    const SAMPLES: usize = 1 << 12;
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let v = Arc::new((0..SAMPLES).map(|_| <Bn256 as ScalarEngine>::Fr::rand(rng).into_repr()).collect::<Vec<_>>());
+    let g = Arc::new((0..SAMPLES).map(|_| <Bn256 as Engine>::G1::rand(rng).into_affine()).collect::<Vec<_>>());
+
+    let pool = Worker::new();
+
+    let expected = multiexp(&pool, (g.clone(), 0), FullDensity, v.clone()).wait().unwrap();
+
+    let observer = Arc::new(CountingObserver {
+        started: AtomicUsize::new(0),
+        finished: AtomicUsize::new(0)
+    });
+
+    let observed = multiexp_with_observer(
+        &pool,
+        (g, 0),
+        FullDensity,
+        v,
+        observer.clone(),
+        CancellationToken::new()
+    ).wait().unwrap();
+
+    assert_eq!(expected, observed);
+    assert!(observer.started.load(Ordering::SeqCst) > 0);
+    assert_eq!(observer.started.load(Ordering::SeqCst), observer.finished.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_multiexp_cancellation() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::bn256::Bn256;
+
+    // cancels the whole job as soon as any region starts
+    struct CancellingObserver {
+        token: CancellationToken
+    }
+
+    impl MultiexpObserver for CancellingObserver {
+        fn on_chunk_started(&self, _skip: u32, _c: u32) {
+            self.token.cancel();
+        }
+
+        fn on_chunk_finished(&self, _skip: u32) {}
+    }
+
+    const SAMPLES: usize = 1 << 12;
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let v = Arc::new((0..SAMPLES).map(|_| <Bn256 as ScalarEngine>::Fr::rand(rng).into_repr()).collect::<Vec<_>>());
+    let g = Arc::new((0..SAMPLES).map(|_| <Bn256 as Engine>::G1::rand(rng).into_affine()).collect::<Vec<_>>());
+
+    let pool = Worker::new();
+
+    let token = CancellationToken::new();
+    let observer = Arc::new(CancellingObserver {
+        token: token.clone()
+    });
+
+    let result = multiexp_with_observer(&pool, (g, 0), FullDensity, v, observer, token.clone()).wait();
+
+    assert!(token.is_cancelled());
+    match result {
+        Err(SynthesisError::Aborted) => {},
+        _ => panic!("multiexp must be aborted")
+    }
+}
+
+#[test]
+fn test_multiexp_from_field_elements() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::bn256::Bn256;
+
+    type Fr = <Bn256 as ScalarEngine>::Fr;
+
+    const SAMPLES: usize = 1 << 12;
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let mut scalars = (0..SAMPLES).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    // make sure that trivial scalars are also covered
+    scalars[0] = Fr::zero();
+    scalars[1] = Fr::one();
+
+    let g = Arc::new((0..SAMPLES).map(|_| <Bn256 as Engine>::G1::rand(rng).into_affine()).collect::<Vec<_>>());
+    let v = Arc::new(scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>());
+
+    let pool = Worker::new();
+    let scalars = Arc::new(scalars);
+
+    let expected = multiexp(&pool, (g.clone(), 0), FullDensity, v).wait().unwrap();
+    let from_field_elements = multiexp_from_field_elements(&pool, (g.clone(), 0), FullDensity, scalars.clone()).wait().unwrap();
+
+    assert_eq!(expected, from_field_elements);
+
+    // regions are carried over between many blocks, including a partial one
+    let in_blocks = multiexp_from_field_elements_with_block_size(&pool, (g, 0), FullDensity, scalars, 100).wait().unwrap();
+
+    assert_eq!(expected, in_blocks);
+}
+
+#[test]
+#[ignore]
+fn test_multiexp_from_field_elements_memory() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::bn256::Bn256;
+    use crate::tests::counting_allocator::measure_peak_memory;
+
+    type Fr = <Bn256 as ScalarEngine>::Fr;
+
+    // run with --test-threads=1, as the allocator is shared by all the tests
+    const SAMPLES: usize = 1 << 20;
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let scalars = Arc::new((0..SAMPLES).map(|_| Fr::rand(rng)).collect::<Vec<_>>());
+    let g = Arc::new(vec![<Bn256 as Engine>::G1Affine::one(); SAMPLES]);
+
+    let pool = Worker::new();
+
+    let (expected, reprs_peak) = measure_peak_memory(|| {
+        let v = Arc::new(scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>());
+
+        multiexp(&pool, (g.clone(), 0), FullDensity, v).wait().unwrap()
+    });
+
+    let (from_field_elements, field_elements_peak) = measure_peak_memory(|| {
+        multiexp_from_field_elements(&pool, (g.clone(), 0), FullDensity, scalars.clone()).wait().unwrap()
+    });
+
+    assert_eq!(expected, from_field_elements);
+
+    println!("Peak memory of multiexp over representations: {} bytes", reprs_peak);
+    println!("Peak memory of multiexp over field elements: {} bytes", field_elements_peak);
+
+    // all the regions run at the same time, so their buckets may be alive together,
+    // but the full copy of the scalars is never allocated, only small scratch buffers
+    let reprs_size = SAMPLES * std::mem::size_of::<<Fr as PrimeField>::Repr>();
+    let buckets_size = window_schedule(Fr::NUM_BITS, window_size(SAMPLES)).iter()
+        .map(|&(_, width)| ((1 << width) - 1) * std::mem::size_of::<<Bn256 as Engine>::G1>())
+        .sum::<usize>();
+
+    assert!(reprs_peak >= reprs_size);
+    assert!(field_elements_peak < buckets_size + reprs_size / 2);
+}
+
+#[test]
+fn test_signed_multiexp() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::bn256::Bn256;
+
+    type Fr = <Bn256 as ScalarEngine>::Fr;
+
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let pool = Worker::new();
+
+    for &samples in [1usize, 7, 1 << 10].iter() {
+        let mut scalars = (0..samples).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        if samples > 3 {
+            let mut minus_one = Fr::one();
+            minus_one.negate();
+
+            scalars[0] = Fr::zero();
+            scalars[1] = Fr::one();
+            scalars[2] = minus_one;
+        }
+
+        let v = Arc::new(scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>());
+        let g = Arc::new((0..samples).map(|_| <Bn256 as Engine>::G1::rand(rng).into_affine()).collect::<Vec<_>>());
+
+        let expected = multiexp(&pool, (g.clone(), 0), FullDensity, v.clone()).wait().unwrap();
+        let signed = multiexp_signed(&pool, (g, 0), v).wait().unwrap();
+
+        assert_eq!(expected, signed);
+    }
+}
+
+#[test]
+#[ignore]
+fn test_signed_multiexp_speed_with_bn256() {
+    use rand::{self, Rand};
+    use crate::pairing::bn256::Bn256;
+
+    const SAMPLES: usize = 1 << 20;
+
+    let rng = &mut rand::thread_rng();
+    let v = Arc::new((0..SAMPLES).map(|_| <Bn256 as ScalarEngine>::Fr::rand(rng).into_repr()).collect::<Vec<_>>());
+    let g = Arc::new((0..SAMPLES).map(|_| <Bn256 as Engine>::G1::rand(rng).into_affine()).collect::<Vec<_>>());
+
+    let pool = Worker::new();
+
+    let start = std::time::Instant::now();
+    let unsigned = multiexp(&pool, (g.clone(), 0), FullDensity, v.clone()).wait().unwrap();
+    println!("{} ns for unsigned multiexp for {} samples", start.elapsed().as_nanos(), SAMPLES);
+
+    let start = std::time::Instant::now();
+    let signed = multiexp_signed(&pool, (g, 0), v).wait().unwrap();
+    println!("{} ns for signed multiexp for {} samples", start.elapsed().as_nanos(), SAMPLES);
+
+    assert_eq!(unsigned, signed);
+}
+
+#[test]
+fn test_dense_multiexp_determinism_across_workers() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::bn256::Bn256;
+
+    const SAMPLES: usize = 1 << 10;
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let v = (0..SAMPLES).map(|_| <Bn256 as ScalarEngine>::Fr::rand(rng).into_repr()).collect::<Vec<_>>();
+    let g = (0..SAMPLES).map(|_| <Bn256 as Engine>::G1::rand(rng).into_affine()).collect::<Vec<_>>();
+
+    let reference = dense_multiexp(&Worker::new_deterministic(1, None), &g, &v).unwrap();
+
+    for &(cpus, chunk) in [(4, None), (4, Some(16)), (3, Some(100)), (8, Some(SAMPLES + 1))].iter() {
+        let pool = Worker::new_deterministic(cpus, chunk);
+        assert_eq!(reference, dense_multiexp(&pool, &g, &v).unwrap());
+    }
+}
+
+#[test]
+fn test_multiexp_parts_recombination() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::bn256::Bn256;
+
+    const SAMPLES: usize = 1 << 10;
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let v = Arc::new((0..SAMPLES).map(|_| <Bn256 as ScalarEngine>::Fr::rand(rng).into_repr()).collect::<Vec<_>>());
+    let g = Arc::new((0..SAMPLES).map(|_| <Bn256 as Engine>::G1::rand(rng).into_affine()).collect::<Vec<_>>());
+
+    let pool = Worker::new();
+
+    let expected = multiexp(&pool, (g.clone(), 0), FullDensity, v.clone()).wait().unwrap();
+
+    let joiner = multiexp(&pool, (g, 0), FullDensity, v);
+    let c = joiner.window_size();
+    let (joined, parts) = joiner.wait_with_parts().unwrap();
+
+    assert_eq!(expected, joined);
+    assert_eq!(expected, join_chunks_from_parts(&parts, c));
+}
+
+#[test]
+fn test_sparse_multiexp() {
+    use rand::{XorShiftRng, SeedableRng, Rand, Rng};
+    use crate::pairing::bn256::Bn256;
+
+    const SAMPLES: usize = 1 << 10;
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let pool = Worker::new();
+
+    for &zeros_percentage in [50u32, 95].iter() {
+        let v = (0..SAMPLES).map(|_| {
+            if rng.gen_range(0, 100) < zeros_percentage {
+                <Bn256 as ScalarEngine>::Fr::zero().into_repr()
+            } else {
+                <Bn256 as ScalarEngine>::Fr::rand(rng).into_repr()
+            }
+        }).collect::<Vec<_>>();
+        let g = (0..SAMPLES).map(|_| <Bn256 as Engine>::G1::rand(rng).into_affine()).collect::<Vec<_>>();
+
+        let mut naive = <Bn256 as Engine>::G1::zero();
+        for (base, exp) in g.iter().zip(v.iter()) {
+            naive.add_assign(&base.mul(*exp));
+        }
+
+        assert_eq!(naive, dense_multiexp(&pool, &g, &v).unwrap());
+
+        let full = multiexp(&pool, (Arc::new(g.clone()), 0), FullDensity, Arc::new(v.clone())).wait().unwrap();
+        assert_eq!(naive, full);
+
+        // only the bases with density set are present in the source
+        let mut density = DensityTracker::new();
+        let mut dense_bases = vec![];
+        let mut expected = <Bn256 as Engine>::G1::zero();
+        for (i, (base, exp)) in g.iter().zip(v.iter()).enumerate() {
+            density.add_element();
+            if rng.gen() {
+                density.inc(i);
+                dense_bases.push(*base);
+                expected.add_assign(&base.mul(*exp));
+            }
+        }
+
+        let dense_bases = Arc::new(dense_bases);
+        let density = Arc::new(density);
+        let v = Arc::new(v);
+
+        let tracked = multiexp(&pool, (dense_bases.clone(), 0), density.clone(), v.clone()).wait().unwrap();
+        assert_eq!(expected, tracked);
+
+        // positions of the bases are carried over between many small chunks
+        let small_chunks = Worker::new_deterministic(3, Some(37));
+        let (tracked, _) = multiexp(&small_chunks, (dense_bases, 0), density, v).wait_with_parts().unwrap();
+        assert_eq!(expected, tracked);
+    }
+}
+
+#[test]
+#[ignore]
+fn test_sparse_multiexp_speed_with_bn256() {
+    use rand::{XorShiftRng, SeedableRng, Rand, Rng};
+    use crate::pairing::bn256::Bn256;
+
+    const SAMPLES: usize = 1 << 20;
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let v = Arc::new((0..SAMPLES).map(|_| {
+        if rng.gen_range(0, 10) != 0 {
+            <Bn256 as ScalarEngine>::Fr::zero().into_repr()
+        } else {
+            <Bn256 as ScalarEngine>::Fr::rand(rng).into_repr()
+        }
+    }).collect::<Vec<_>>());
+    let g = Arc::new((0..SAMPLES).map(|_| <Bn256 as Engine>::G1::rand(rng).into_affine()).collect::<Vec<_>>());
+
+    let pool = Worker::new();
+
+    let start = std::time::Instant::now();
+    let sparse = multiexp(&pool, (g.clone(), 0), FullDensity, v.clone()).wait().unwrap();
+    println!("{} ns for multiexp with 90% zeroes for {} samples", start.elapsed().as_nanos(), SAMPLES);
+
+    // force the path without compaction
+    let start = std::time::Instant::now();
+    let regular = multiexp_with(MultiexpAlgorithm::default(), None, &pool, (g, 0), FullDensity, v).wait().unwrap();
+    println!("{} ns for regular multiexp for {} samples", start.elapsed().as_nanos(), SAMPLES);
+
+    assert_eq!(sparse, regular);
+}
+
+#[test]
+#[ignore]
+fn test_sparse_multiexp_memory() {
+    use rand::{XorShiftRng, SeedableRng, Rand, Rng};
+    use crate::pairing::bn256::Bn256;
+    use crate::tests::counting_allocator::measure_peak_memory;
+
+    type Fr = <Bn256 as ScalarEngine>::Fr;
+
+    // run with --test-threads=1, as the allocator is shared by all the tests
+    const SAMPLES: usize = 1 << 20;
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let v = Arc::new((0..SAMPLES).map(|_| {
+        if rng.gen_range(0, 10) != 0 {
+            Fr::zero().into_repr()
+        } else {
+            Fr::rand(rng).into_repr()
+        }
+    }).collect::<Vec<_>>());
+    let g = Arc::new(vec![<Bn256 as Engine>::G1Affine::one(); SAMPLES]);
+
+    let mut density = DensityTracker::new();
+    for i in 0..SAMPLES {
+        density.add_element();
+        density.inc(i);
+    }
+    let density = Arc::new(density);
+
+    let pool = Worker::new();
+
+    let (sparse, sparse_peak) = measure_peak_memory(|| {
+        multiexp(&pool, (g.clone(), 0), density.clone(), v.clone()).wait().unwrap()
+    });
+
+    let regular = multiexp_with(MultiexpAlgorithm::default(), None, &pool, (g, 0), density, v).wait().unwrap();
+    assert_eq!(sparse, regular);
+
+    println!("Peak memory of multiexp with 90% zeroes: {} bytes", sparse_peak);
+
+    // the prover runs several multiexps over the same exponents, so compaction
+    // must not copy them, only keep small positions of the nonzero ones
+    let reprs_size = SAMPLES * std::mem::size_of::<<Fr as PrimeField>::Repr>();
+    let buckets_size = window_schedule(Fr::NUM_BITS, window_size(SAMPLES)).iter()
+        .map(|&(_, width)| ((1 << width) - 1) * std::mem::size_of::<<Bn256 as Engine>::G1>())
+        .sum::<usize>();
+
+    assert!(sparse_peak < buckets_size + reprs_size / 8);
+}
+
+#[test]
+fn test_multiexp_algorithms_and_window_sizes() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::bn256::Bn256;
+
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let pool = Worker::new();
+
+    for &samples in [7, 1 << 10, 1 << 12].iter() {
+        let v = Arc::new((0..samples).map(|_| <Bn256 as ScalarEngine>::Fr::rand(rng).into_repr()).collect::<Vec<_>>());
+        let g = Arc::new((0..samples).map(|_| <Bn256 as Engine>::G1::rand(rng).into_affine()).collect::<Vec<_>>());
+
+        let mut naive = <Bn256 as Engine>::G1::zero();
+        for (base, exp) in g.iter().zip(v.iter()) {
+            naive.add_assign(&base.mul(*exp));
+        }
+
+        for &algorithm in [MultiexpAlgorithm::Buckets, MultiexpAlgorithm::BucketsPrefetch].iter() {
+            for &c in [None, Some(1), Some(3), Some(7), Some(11)].iter() {
+                let result = multiexp_with(algorithm, c, &pool, (g.clone(), 0), FullDensity, v.clone()).wait().unwrap();
+
+                assert_eq!(naive, result, "algorithm {:?} with window {:?} on {} samples", algorithm, c, samples);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_window_schedule() {
+    assert_eq!(window_schedule(254, 7).last(), Some(&(252, 2)));
+    assert_eq!(window_schedule(254, 9).last(), Some(&(252, 2)));
+    assert_eq!(window_schedule(254, 11).last(), Some(&(253, 1)));
+    assert_eq!(window_schedule(255, 3).last(), Some(&(252, 3)));
+    assert_eq!(window_schedule(8, 3), vec![(0, 3), (3, 3), (6, 2)]);
+
+    for c in 1..20 {
+        let schedule = window_schedule(254, c);
+        assert_eq!(schedule.iter().map(|w| w.1).sum::<u32>(), 254);
+        assert!(schedule.windows(2).all(|w| w[0].0 + w[0].1 == w[1].0));
+    }
+}
+
+#[test]
+fn test_multiexp_with_partial_top_window() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::bn256::{Bn256, Fr};
+
+    const SAMPLES: usize = 1 << 8;
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    assert_eq!(Fr::NUM_BITS, 254);
+
+    // scalars with the highest bits set exercise the narrow top window
+    let mut minus_one = Fr::one();
+    minus_one.negate();
+    let mut scalars = vec![minus_one, Fr::zero(), Fr::one()];
+    scalars.extend((0..(SAMPLES - 3)).map(|_| Fr::rand(rng)));
+
+    let v = Arc::new(scalars.into_iter().map(|s| s.into_repr()).collect::<Vec<_>>());
+    let g = Arc::new((0..SAMPLES).map(|_| <Bn256 as Engine>::G1::rand(rng).into_affine()).collect::<Vec<_>>());
+
+    let mut naive = <Bn256 as Engine>::G1::zero();
+    for (base, exp) in g.iter().zip(v.iter()) {
+        naive.add_assign(&base.mul(*exp));
+    }
+
+    let pool = Worker::new();
+
+    for &c in [7, 9, 11].iter() {
+        let joiner = multiexp_with(MultiexpAlgorithm::Buckets, Some(c), &pool, (g.clone(), 0), FullDensity, v.clone());
+        assert_eq!(joiner.window_schedule(), &window_schedule(254, c)[..]);
+
+        let schedule = joiner.window_schedule().to_vec();
+        let (joined, parts) = joiner.wait_with_parts().unwrap();
+
+        assert_eq!(naive, joined);
+        assert_eq!(naive, join_chunks_by_schedule(&parts, &schedule));
+    }
+}
+
+#[test]
+fn test_multiexp_reduces_unreduced_exponents() {
+    use rand::{XorShiftRng, SeedableRng, Rand, Rng};
+    use crate::pairing::bn256::{Bn256, Fr, FrRepr};
+
+    const SAMPLES: usize = 1 << 6;
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let pool = Worker::new();
+
+    for _ in 0..4 {
+        // the modulus itself, the modulus plus one, representations with all the bits
+        // set and random ones with the bits above NUM_BITS set
+        let mut modulus_plus_one = Fr::char();
+        modulus_plus_one.add_nocarry(&FrRepr::from(1));
+
+        let mut v = vec![Fr::char(), modulus_plus_one, FrRepr([u64::max_value(); 4])];
+        v.extend((0..(SAMPLES - v.len())).map(|_| {
+            let mut repr = Fr::rand(rng).into_repr();
+            if rng.gen() {
+                repr.as_mut()[3] |= rng.gen::<u64>() << 62;
+            }
+
+            repr
+        }));
+        rng.shuffle(&mut v);
+
+        let g = (0..SAMPLES).map(|_| <Bn256 as Engine>::G1::rand(rng).into_affine()).collect::<Vec<_>>();
+
+        // reduce by subtracting the modulus
+        let mut naive = <Bn256 as Engine>::G1::zero();
+        for (base, exp) in g.iter().zip(v.iter()) {
+            let mut exp = *exp;
+            while exp >= Fr::char() {
+                exp.sub_noborrow(&Fr::char());
+            }
+
+            naive.add_assign(&base.mul(exp));
+        }
+
+        let v = Arc::new(v);
+        let g = Arc::new(g);
+
+        assert_eq!(naive, dense_multiexp(&pool, &g, &v).unwrap());
+        assert_eq!(naive, multiexp(&pool, (g.clone(), 0), FullDensity, v.clone()).wait().unwrap());
+        assert_eq!(naive, multiexp_signed(&pool, (g.clone(), 0), v.clone()).wait().unwrap());
+
+        for &c in [7, 9, 11].iter() {
+            let joined = multiexp_with(MultiexpAlgorithm::Buckets, Some(c), &pool, (g.clone(), 0), FullDensity, v.clone()).wait().unwrap();
+            assert_eq!(naive, joined);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Commonly used traits, types and entry points of the crate. Downstream code
+//! should prefer importing `bellman_ce::prelude::*` over the paths of the
+//! individual modules, which may be reorganized.
+
+#[doc(inline)]
+pub use crate::{
+    Circuit,
+    ConstraintSystem,
+    SynthesisError,
+    Variable,
+    Index,
+    LinearCombination,
+    Namespace
+};
+
+#[doc(inline)]
+pub use crate::pairing::{
+    Engine,
+    CurveAffine,
+    CurveProjective
+};
+
+#[doc(inline)]
+pub use crate::pairing::ff::{
+    Field,
+    PrimeField,
+    PrimeFieldRepr,
+    ScalarEngine
+};
+
+#[doc(inline)]
+pub use crate::worker::Worker;
+
+#[doc(inline)]
+pub use crate::source::{
+    SourceBuilder,
+    FullDensity,
+    DensityTracker
+};
+
+#[doc(inline)]
+pub use crate::multiexp::{
+    multiexp,
+    multiexp_with_observer,
+    multiexp_signed,
+    multiexp_from_field_elements,
+    dense_multiexp,
+    MultiexpObserver,
+    CancellationToken
+};
+
+#[doc(inline)]
+pub use crate::domain::EvaluationDomain;
+
+#[doc(inline)]
+pub use crate::groth16::{
+    Parameters,
+    VerifyingKey,
+    PreparedVerifyingKey,
+    Proof,
+    generate_random_parameters,
+    create_random_proof,
+    prepare_verifying_key,
+    prepare_verifying_key_checked,
+    verify_proof,
+    verify_proof_unchecked,
+    verify_proofs_batched
+};
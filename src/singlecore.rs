@@ -3,6 +3,7 @@
 extern crate futures;
 
 use std::marker::PhantomData;
+use std::cell::RefCell;
 
 use std::future::{Future};
 use std::task::{Context, Poll};
@@ -11,9 +12,15 @@ use std::pin::{Pin};
 use self::futures::channel::oneshot::{channel, Sender, Receiver};
 use self::futures::executor::{block_on};
 
+thread_local! {
+    // worker installed by `Worker::enter` on this thread
+    static CURRENT_WORKER: RefCell<Option<Worker>> = RefCell::new(None);
+}
+
 #[derive(Clone)]
 pub struct Worker {
     cpus: usize,
+    chunk_size_override: Option<usize>,
 }
 
 impl Worker {
@@ -23,6 +30,7 @@ impl Worker {
     pub(crate) fn new_with_cpus(_cpus: usize) -> Worker {
         Worker {
             cpus: 1,
+            chunk_size_override: None,
         }
     }
 
@@ -30,6 +38,48 @@ impl Worker {
         Self::new_with_cpus(1)
     }
 
+    /// Mirrors the multicore API. Work is always done on one thread,
+    /// but `scope` still honors the chunk size override.
+    pub fn new_deterministic(num_cpus: usize, chunk_size_override: Option<usize>) -> Worker {
+        assert!(num_cpus > 0, "worker requires at least one thread");
+        if let Some(chunk_size) = chunk_size_override {
+            assert!(chunk_size > 0, "chunk size must be positive");
+        }
+
+        Worker {
+            cpus: 1,
+            chunk_size_override: chunk_size_override,
+        }
+    }
+
+    /// Returns the worker installed on this thread by `enter`, if any
+    pub fn try_current() -> Option<Worker> {
+        CURRENT_WORKER.with(|w| w.borrow().clone())
+    }
+
+    /// Returns the worker installed on this thread by `enter` or creates a new one
+    pub fn current_or_new() -> Worker {
+        Self::try_current().unwrap_or_else(Self::new)
+    }
+
+    /// Installs this worker as the current one on this thread while `f` runs
+    pub fn enter<F, R>(&self, f: F) -> R
+        where F: FnOnce() -> R
+    {
+        struct Restore(Option<Worker>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let previous = self.0.take();
+                CURRENT_WORKER.with(|w| *w.borrow_mut() = previous);
+            }
+        }
+
+        let _restore = Restore(CURRENT_WORKER.with(|w| w.replace(Some(self.clone()))));
+
+        f()
+    }
+
     pub fn log_num_cpus(&self) -> u32 {
         0u32
     }
@@ -60,7 +110,13 @@ impl Worker {
     ) -> R
         where F: FnOnce(&Scope<'a>, usize) -> R
     {
-        let chunk_size = if elements == 0 { 1 } else { elements };
+        let chunk_size = if let Some(chunk_size) = self.chunk_size_override {
+            chunk_size
+        } else if elements == 0 {
+            1
+        } else {
+            elements
+        };
 
         let scope = Scope{
             _marker: PhantomData
@@ -420,7 +420,7 @@ pub fn generate_srs<E: Engine>(
 
     let x_inverse = x.inverse().ok_or(SynthesisError::UnexpectedIdentity)?;
 
-    let worker = Worker::new();
+    let worker = Worker::current_or_new();
 
     let mut x_powers_positive = vec![Scalar::<E>(E::Fr::zero()); d];
     let mut x_powers_negative = vec![Scalar::<E>(E::Fr::zero()); d];
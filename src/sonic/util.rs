@@ -158,7 +158,7 @@ pub fn evaluate_at_consequitive_powers<'a, F: Field> (
 
     let (s, r) = unbounded();
 
-    let worker = Worker::new();
+    let worker = Worker::current_or_new();
 
     worker.scope(coeffs.len(), |scope, chunk| {
         for (i, coeffs) in coeffs.chunks(chunk).enumerate()
@@ -209,7 +209,7 @@ pub fn mut_evaluate_at_consequitive_powers<'a, F: Field> (
 
     let (s, r) = unbounded();
 
-    let worker = Worker::new();
+    let worker = Worker::current_or_new();
 
     worker.scope(coeffs.len(), |scope, chunk| {
         for (i, coeffs) in coeffs.chunks_mut(chunk).enumerate()
@@ -259,7 +259,7 @@ pub fn mut_distribute_consequitive_powers<'a, F: Field> (
     {
     use crate::multicore::Worker;
 
-    let worker = Worker::new();
+    let worker = Worker::current_or_new();
 
     worker.scope(coeffs.len(), |scope, chunk| {
         for (i, coeffs_chunk) in coeffs.chunks_mut(chunk).enumerate()
@@ -345,7 +345,7 @@ where
 
     assert_eq!(s.len(), g.len(), "scalars and exponents must have the same length");
 
-    let pool = Worker::new();
+    let pool = Worker::current_or_new();
 
     // use std::time::Instant;
     // let start = Instant::now();
@@ -633,7 +633,7 @@ pub fn multiply_polynomials<E: Engine>(a: Vec<E::Fr>, b: Vec<E::Fr>) -> Vec<E::F
     use crate::multicore::Worker;
     use crate::domain::{EvaluationDomain, Scalar};
 
-    let worker = Worker::new();
+    let worker = Worker::current_or_new();
     let scalars_a: Vec<Scalar<E>> = a.into_iter().map(|e| Scalar::<E>(e)).collect();
     let mut domain_a = EvaluationDomain::from_coeffs_into_sized(scalars_a, result_len).unwrap();
 
@@ -696,7 +696,7 @@ pub fn multiply_polynomials_fft<E: Engine>(a: Vec<E::Fr>, b: Vec<E::Fr>) -> Vec<
     let omegainv = omega.inverse().unwrap();
     let minv = E::Fr::from_str(&format!("{}", m)).unwrap().inverse().unwrap();
 
-    let worker = Worker::new();
+    let worker = Worker::current_or_new();
 
     let mut scalars_a: Vec<Scalar<E>> = a.into_iter().map(|e| Scalar::<E>(e)).collect();
     let mut scalars_b: Vec<Scalar<E>> = b.into_iter().map(|e| Scalar::<E>(e)).collect();
@@ -794,7 +794,7 @@ pub fn add_polynomials<F: Field>(a: &mut [F], b: &[F]) {
         use crate::multicore::Worker;
         use crate::domain::{EvaluationDomain, Scalar};
 
-        let worker = Worker::new();
+        let worker = Worker::current_or_new();
 
         assert_eq!(a.len(), b.len());
 
@@ -815,7 +815,7 @@ pub fn sub_polynomials<F: Field>(a: &mut [F], b: &[F]) {
     use crate::multicore::Worker;
     use crate::domain::{EvaluationDomain, Scalar};
 
-    let worker = Worker::new();
+    let worker = Worker::current_or_new();
 
     assert_eq!(a.len(), b.len());
 
@@ -836,7 +836,7 @@ pub fn mul_polynomial_by_scalar<F: Field>(a: &mut [F], b: F) {
         use crate::multicore::Worker;
         use crate::domain::{EvaluationDomain, Scalar};
 
-        let worker = Worker::new();
+        let worker = Worker::current_or_new();
 
         worker.scope(a.len(), |scope, chunk| {
             for a in a.chunks_mut(chunk)
@@ -856,7 +856,7 @@ pub fn mul_add_polynomials<F: Field>(a: &mut [F], b: &[F], c: F) {
         use crate::multicore::Worker;
         use crate::domain::{EvaluationDomain, Scalar};
 
-        let worker = Worker::new();
+        let worker = Worker::current_or_new();
 
         assert_eq!(a.len(), b.len());
 
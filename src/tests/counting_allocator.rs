@@ -0,0 +1,44 @@
+//! Global allocator of the test binary that keeps track of the allocated memory,
+//! so tests can compare the peak memory usage of different code paths. It's shared
+//! by all the tests, so measurements are only meaningful with `--test-threads=1`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::SeqCst);
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Runs `f` and returns its result together with the peak amount of memory
+/// that was allocated on top of the memory allocated before the call
+pub(crate) fn measure_peak_memory<T, F: FnOnce() -> T>(f: F) -> (T, usize) {
+    let before = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(before, Ordering::SeqCst);
+
+    let result = f();
+
+    let peak = PEAK.load(Ordering::SeqCst);
+
+    (result, peak.saturating_sub(before))
+}
@@ -8,6 +8,7 @@ use crate::pairing::ff:: {
 };
 
 pub mod dummy_engine;
+pub(crate) mod counting_allocator;
 use self::dummy_engine::*;
 
 use std::marker::PhantomData;
@@ -91,3 +92,27 @@ impl<E: Engine> Circuit<E> for XORDemo<E> {
     }
 }
 
+
+/// Environment variable that marks the process started by `run_isolated`
+const ISOLATED_TEST_VAR: &str = "BELLMAN_ISOLATED_TEST";
+
+/// Runs the ignored test with the full path `name` alone in a new process of the test
+/// binary, so that it can rely on process-wide state, like the number of created thread
+/// pools, without being disturbed by the tests running in parallel
+pub(crate) fn run_isolated(name: &str) {
+    let output = std::process::Command::new(std::env::current_exe().expect("must find the test binary"))
+        .args(&[name, "--exact", "--ignored", "--test-threads=1", "--nocapture"])
+        .env(ISOLATED_TEST_VAR, name)
+        .output()
+        .expect("must run the test binary");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "isolated test {} failed:\n{}{}", name, stdout, String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("1 passed"), "isolated test {} was not found:\n{}", name, stdout);
+}
+
+/// Whether the current process was started by `run_isolated` for the test `name`.
+/// Isolated tests should do nothing otherwise, e.g. when all the ignored tests are run together
+pub(crate) fn is_isolated(name: &str) -> bool {
+    std::env::var(ISOLATED_TEST_VAR).map(|v| v == name).unwrap_or(false)
+}
@@ -0,0 +1,104 @@
+//! Single base scalar multiplication using the windowed non-adjacent form (wNAF).
+//! It's noticeably faster than the double-and-add of `CurveAffine::mul` and is intended
+//! for the handful of multiplications that are not worth a full multiexp, e.g. in
+//! verification. Both helpers are thin wrappers over `Wnaf` from the pairing crate,
+//! which picks the window size for the scalar or for the number of scalars.
+
+use crate::pairing::{
+    CurveAffine,
+    CurveProjective,
+    Wnaf
+};
+
+use crate::pairing::ff::{
+    PrimeField
+};
+
+/// Computes `scalar * base` using wNAF
+pub fn mul_wnaf<G: CurveAffine>(
+    base: &G,
+    scalar: &<G::Scalar as PrimeField>::Repr
+) -> G::Projective
+{
+    let mut wnaf = Wnaf::<(), Vec<G::Projective>, Vec<i64>>::new();
+
+    wnaf.scalar(*scalar).base(base.into_projective())
+}
+
+/// Computes `scalars[i] * base` for every scalar, sharing the precomputed table
+/// of multiples of the `base`
+pub fn mul_many_wnaf<G: CurveAffine>(
+    base: &G,
+    scalars: &[<G::Scalar as PrimeField>::Repr]
+) -> Vec<G::Projective>
+{
+    let mut wnaf = Wnaf::<(), Vec<G::Projective>, Vec<i64>>::new();
+    let mut wnaf = wnaf.base(base.into_projective(), scalars.len());
+
+    scalars.iter().map(|s| wnaf.scalar(*s)).collect()
+}
+
+#[test]
+fn test_wnaf_multiplication() {
+    use rand::{XorShiftRng, SeedableRng, Rand};
+    use crate::pairing::bn256::{Fr, G1, G2};
+    use crate::pairing::ff::Field;
+
+    let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+    let mut minus_one = Fr::one();
+    minus_one.negate();
+
+    let mut scalars = vec![Fr::zero(), Fr::one(), minus_one];
+    scalars.extend((0..32).map(|_| Fr::rand(rng)));
+    let scalars = scalars.into_iter().map(|s| s.into_repr()).collect::<Vec<_>>();
+
+    let g1 = G1::rand(rng).into_affine();
+    let g2 = G2::rand(rng).into_affine();
+
+    for s in scalars.iter() {
+        assert_eq!(mul_wnaf(&g1, s), g1.mul(*s));
+        assert_eq!(mul_wnaf(&g2, s), g2.mul(*s));
+    }
+
+    // window size depends on the number of scalars
+    for &num_scalars in [1, 3, scalars.len()].iter() {
+        let many = mul_many_wnaf(&g1, &scalars[..num_scalars]);
+        for (s, r) in scalars.iter().zip(many.into_iter()) {
+            assert_eq!(r, g1.mul(*s));
+        }
+    }
+}
+
+#[test]
+#[ignore]
+fn test_wnaf_multiplication_speed() {
+    use rand::{self, Rand};
+    use crate::pairing::bn256::{Fr, G1};
+
+    const SAMPLES: usize = 1 << 12;
+
+    let rng = &mut rand::thread_rng();
+    let scalars = (0..SAMPLES).map(|_| Fr::rand(rng).into_repr()).collect::<Vec<_>>();
+    let g1 = G1::rand(rng).into_affine();
+
+    let start = std::time::Instant::now();
+    for s in scalars.iter() {
+        let _ = g1.mul(*s);
+    }
+    let mul_ns = start.elapsed().as_nanos() as f64 / (SAMPLES as f64);
+
+    let start = std::time::Instant::now();
+    for s in scalars.iter() {
+        let _ = mul_wnaf(&g1, s);
+    }
+    let wnaf_ns = start.elapsed().as_nanos() as f64 / (SAMPLES as f64);
+
+    let start = std::time::Instant::now();
+    let _ = mul_many_wnaf(&g1, &scalars);
+    let many_wnaf_ns = start.elapsed().as_nanos() as f64 / (SAMPLES as f64);
+
+    println!("Double-and-add: {} ns per multiplication", mul_ns);
+    println!("wNAF: {} ns per multiplication, speedup {}", wnaf_ns, mul_ns / wnaf_ns);
+    println!("wNAF with shared table: {} ns per multiplication, speedup {}", many_wnaf_ns, mul_ns / many_wnaf_ns);
+}
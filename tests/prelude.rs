@@ -0,0 +1,70 @@
+// Uses nothing but the prelude (and a concrete curve) to make sure
+// that it stays sufficient for the common flow.
+use bellman_ce::prelude::*;
+
+use bellman_ce::pairing::bls12_381::{Bls12, Fr};
+
+use rand::thread_rng;
+
+struct CubeDemo<E: Engine> {
+    x: Option<E::Fr>
+}
+
+impl<E: Engine> Circuit<E> for CubeDemo<E> {
+    fn synthesize<CS: ConstraintSystem<E>>(
+        self,
+        cs: &mut CS
+    ) -> Result<(), SynthesisError>
+    {
+        let x_value = self.x;
+        let x = cs.alloc(|| "x", || x_value.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let x_squared_value = x_value.map(|mut x| {
+            x.square();
+            x
+        });
+        let x_squared = cs.alloc(|| "x^2", || x_squared_value.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce(
+            || "x * x = x^2",
+            |lc| lc + x,
+            |lc| lc + x,
+            |lc| lc + x_squared
+        );
+
+        let cube = cs.alloc_input(|| "x^3", || {
+            let mut tmp = x_squared_value.ok_or(SynthesisError::AssignmentMissing)?;
+            tmp.mul_assign(&x_value.ok_or(SynthesisError::AssignmentMissing)?);
+
+            Ok(tmp)
+        })?;
+
+        cs.enforce(
+            || "x^2 * x = x^3",
+            |lc| lc + x_squared,
+            |lc| lc + x,
+            |lc| lc + cube
+        );
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_prelude_is_sufficient() {
+    let rng = &mut thread_rng();
+
+    let params = generate_random_parameters::<Bls12, _, _>(CubeDemo { x: None }, rng).unwrap();
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let x = Fr::from_str("3").unwrap();
+    let cube = Fr::from_str("27").unwrap();
+
+    let proof = create_random_proof(CubeDemo::<Bls12> { x: Some(x) }, &params, rng).unwrap();
+
+    assert!(verify_proof(&pvk, &proof, &[cube]).unwrap());
+    assert!(!verify_proof(&pvk, &proof, &[x]).unwrap());
+
+    let worker = Worker::new();
+    assert!(verify_proofs_batched(&pvk, &[(proof, vec![cube])], rng, &worker).unwrap());
+}